@@ -0,0 +1,455 @@
+//! Asynchronous counterparts of [`TextIOHandler`] and its implementors,
+//! available with the `async` feature.
+
+use std::ffi::{OsStr, OsString};
+use std::future::Future;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::sync::{Arc, Mutex};
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::runtime::{Builder as RuntimeBuilder, Runtime};
+use tokio::task;
+use crate::error::annotate;
+use crate::{glob, FileTextHandler, MockTextHandler, SharedMockTextHandler, TextIOHandler, TextOperation};
+
+/// The asynchronous counterpart of [`TextIOHandler`], with the same methods and semantics.
+pub trait AsyncTextIOHandler: Send + Sync {
+    fn read_text(&self, name: &OsStr) -> impl Future<Output = IoResult<String>> + Send;
+    fn write_text(&mut self, name: &OsStr, content: String) -> impl Future<Output = IoResult<()>> + Send;
+
+    /// Adds `content` to the end of the text with the given name, creating the text if it's missing.
+    fn append_text(&mut self, name: &OsStr, content: String) -> impl Future<Output = IoResult<()>> + Send;
+
+    /// Removes the text with the given name.
+    fn delete_text(&mut self, name: &OsStr) -> impl Future<Output = IoResult<()>> + Send;
+
+    /// Returns whether a text with the given name is present.
+    fn text_exists(&self, name: &OsStr) -> impl Future<Output = IoResult<bool>> + Send;
+
+    /// Moves the text named `from` to the name `to`, replacing any text already present under `to`.
+    fn rename_text(&mut self, from: &OsStr, to: &OsStr) -> impl Future<Output = IoResult<()>> + Send;
+
+    /// Copies the text named `from` to the name `to`, replacing any text already present under `to`.
+    fn copy_text(&mut self, from: &OsStr, to: &OsStr) -> impl Future<Output = IoResult<()>> + Send;
+
+    /// Returns the names of all texts at or below `prefix`, sorted.
+    /// See [`TextIOHandler::list_texts`].
+    fn list_texts(&self, prefix: &OsStr) -> impl Future<Output = IoResult<Vec<OsString>>> + Send;
+
+    /// Returns the names of all texts matching the glob `pattern`, sorted.
+    /// See [`glob_match`](crate::glob_match) for the supported syntax.
+    fn glob_texts(&self, pattern: &str) -> impl Future<Output = IoResult<Vec<OsString>>> + Send {
+        async move {
+            let prefix = glob::literal_prefix(pattern);
+
+            Ok(self.list_texts(OsStr::new(&prefix))
+                .await?
+                .into_iter()
+                .filter(|name| name.to_str().is_some_and(|name| crate::glob_match(pattern, name)))
+                .collect())
+        }
+    }
+
+    /// Creates the directory with the given name and all of its missing parents.
+    fn create_dir_all(&mut self, name: &OsStr) -> impl Future<Output = IoResult<()>> + Send;
+}
+
+/// AsyncFileTextHandler provides asynchronous string read and write operations to file system files,
+/// using [`tokio::fs`].
+/// It behaves like a [`FileTextHandler`] writing in [`WriteMode::Direct`](crate::WriteMode::Direct).
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use string_io_and_mock::{AsyncFileTextHandler, AsyncTextIOHandler};
+///
+/// # tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(async {
+/// let file_name = OsStr::new("tests/playground/asyncText.txt");
+/// let mut fth = AsyncFileTextHandler::new();
+///
+/// fth.write_text(&file_name, String::from("Patience is a virtue.")).await.unwrap();
+///
+/// assert_eq!("Patience is a virtue.", fth.read_text(&file_name).await.unwrap());
+/// # });
+/// ```
+#[derive(Default)]
+pub struct AsyncFileTextHandler {}
+
+impl AsyncFileTextHandler {
+    pub fn new() -> Self {
+        AsyncFileTextHandler {}
+    }
+}
+
+impl AsyncTextIOHandler for AsyncFileTextHandler {
+
+    async fn read_text(&self, name: &OsStr) -> IoResult<String> {
+        annotate(fs::read_to_string(name).await, TextOperation::Read, name, None)
+    }
+
+    async fn write_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        annotate(fs::write(name, content).await, TextOperation::Write, name, None)
+    }
+
+    async fn append_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        let result = async {
+            let mut file = OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(name)
+                .await?;
+
+            file.write_all(content.as_bytes()).await?;
+            file.flush().await
+        };
+
+        annotate(result.await, TextOperation::Append, name, None)
+    }
+
+    async fn delete_text(&mut self, name: &OsStr) -> IoResult<()> {
+        annotate(fs::remove_file(name).await, TextOperation::Delete, name, None)
+    }
+
+    async fn text_exists(&self, name: &OsStr) -> IoResult<bool> {
+        let result = match fs::metadata(name).await {
+            Ok(meta) => Ok(meta.is_file()),
+            Err(io_err) if io_err.kind() == ErrorKind::NotFound => Ok(false),
+            Err(io_err) => Err(io_err),
+        };
+
+        annotate(result, TextOperation::Exists, name, None)
+    }
+
+    async fn rename_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        annotate(fs::rename(from, to).await, TextOperation::Rename, from, Some(to))
+    }
+
+    async fn copy_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        let (from, to) = (from.to_os_string(), to.to_os_string());
+
+        // Copying a file onto itself would truncate it, which FileTextHandler guards against.
+        task::spawn_blocking(move || FileTextHandler::new().copy_text(&from, &to))
+            .await
+            .map_err(IoError::other)?
+    }
+
+    async fn list_texts(&self, prefix: &OsStr) -> IoResult<Vec<OsString>> {
+        let prefix = prefix.to_os_string();
+
+        task::spawn_blocking(move || FileTextHandler::new().list_texts(&prefix))
+            .await
+            .map_err(IoError::other)?
+    }
+
+    async fn glob_texts(&self, pattern: &str) -> IoResult<Vec<OsString>> {
+        let pattern = pattern.to_string();
+
+        task::spawn_blocking(move || FileTextHandler::new().glob_texts(&pattern))
+            .await
+            .map_err(IoError::other)?
+    }
+
+    async fn create_dir_all(&mut self, name: &OsStr) -> IoResult<()> {
+        annotate(fs::create_dir_all(name).await, TextOperation::CreateDir, name, None)
+    }
+}
+
+/// AsyncMockTextHandler is the asynchronous counterpart of [`MockTextHandler`].
+/// As the futures it returns must be `Send`, it wraps a [`SharedMockTextHandler`],
+/// so cloning it yields a handle to the same texts.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use std::io::ErrorKind;
+/// use string_io_and_mock::{AsyncMockTextHandler, AsyncTextIOHandler};
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let mut mock = AsyncMockTextHandler::new();
+///
+/// mock.write_text(OsStr::new("a.txt"), String::from("alpha")).await.unwrap();
+/// assert_eq!("alpha", mock.read_text(OsStr::new("a.txt")).await.unwrap());
+///
+/// let err = mock.read_text(OsStr::new("b.txt")).await.unwrap_err();
+/// assert_eq!(ErrorKind::NotFound, err.kind());
+/// # });
+/// ```
+#[derive(Clone, Default)]
+pub struct AsyncMockTextHandler {
+    mock: SharedMockTextHandler,
+}
+
+impl AsyncMockTextHandler {
+    pub fn new() -> Self {
+        Self::from(MockTextHandler::new())
+    }
+
+    /// Calls `f` with exclusive access to the wrapped [`MockTextHandler`],
+    /// e.g. to inject failures or to query the calls recorded.
+    pub fn with_mock<R, F: FnOnce(&mut MockTextHandler) -> R>(&self, f: F) -> R {
+        self.mock.with_mock(f)
+    }
+}
+
+impl From<MockTextHandler> for AsyncMockTextHandler {
+    fn from(mock: MockTextHandler) -> Self {
+        AsyncMockTextHandler {
+            mock: SharedMockTextHandler::from(mock),
+        }
+    }
+}
+
+impl AsyncTextIOHandler for AsyncMockTextHandler {
+
+    async fn read_text(&self, name: &OsStr) -> IoResult<String> {
+        self.mock.read_text(name)
+    }
+
+    async fn write_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        self.mock.write_text(name, content)
+    }
+
+    async fn append_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        self.mock.append_text(name, content)
+    }
+
+    async fn delete_text(&mut self, name: &OsStr) -> IoResult<()> {
+        self.mock.delete_text(name)
+    }
+
+    async fn text_exists(&self, name: &OsStr) -> IoResult<bool> {
+        self.mock.text_exists(name)
+    }
+
+    async fn rename_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        self.mock.rename_text(from, to)
+    }
+
+    async fn copy_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        self.mock.copy_text(from, to)
+    }
+
+    async fn list_texts(&self, prefix: &OsStr) -> IoResult<Vec<OsString>> {
+        self.mock.list_texts(prefix)
+    }
+
+    async fn create_dir_all(&mut self, name: &OsStr) -> IoResult<()> {
+        self.mock.create_dir_all(name)
+    }
+}
+
+/// SyncToAsyncAdapter makes a [`TextIOHandler`] usable as an [`AsyncTextIOHandler`].
+/// Each call runs on tokio's blocking thread pool, so blocking handlers like [`FileTextHandler`]
+/// don't stall the executor.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use string_io_and_mock::{AsyncTextIOHandler, MockTextHandler, SyncToAsyncAdapter};
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let mut handler = SyncToAsyncAdapter::new(MockTextHandler::new());
+///
+/// handler.write_text(OsStr::new("a.txt"), String::from("alpha")).await.unwrap();
+/// assert_eq!("alpha", handler.read_text(OsStr::new("a.txt")).await.unwrap());
+/// # });
+/// ```
+pub struct SyncToAsyncAdapter<H> {
+    handler: Arc<Mutex<H>>,
+}
+
+impl<H: TextIOHandler + Send + 'static> SyncToAsyncAdapter<H> {
+    pub fn new(handler: H) -> Self {
+        SyncToAsyncAdapter {
+            handler: Arc::new(Mutex::new(handler)),
+        }
+    }
+
+    /// Runs `operation` on the wrapped handler on tokio's blocking thread pool.
+    async fn run<R, F>(&self, operation: F) -> IoResult<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut Arc<Mutex<H>>) -> IoResult<R> + Send + 'static,
+    {
+        let mut handler = Arc::clone(&self.handler);
+
+        task::spawn_blocking(move || operation(&mut handler))
+            .await
+            .map_err(IoError::other)?
+    }
+}
+
+impl<H: TextIOHandler + Send + 'static> AsyncTextIOHandler for SyncToAsyncAdapter<H> {
+
+    async fn read_text(&self, name: &OsStr) -> IoResult<String> {
+        let name = name.to_os_string();
+        self.run(move |handler| handler.read_text(&name)).await
+    }
+
+    async fn write_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        let name = name.to_os_string();
+        self.run(move |handler| handler.write_text(&name, content)).await
+    }
+
+    async fn append_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        let name = name.to_os_string();
+        self.run(move |handler| handler.append_text(&name, content)).await
+    }
+
+    async fn delete_text(&mut self, name: &OsStr) -> IoResult<()> {
+        let name = name.to_os_string();
+        self.run(move |handler| handler.delete_text(&name)).await
+    }
+
+    async fn text_exists(&self, name: &OsStr) -> IoResult<bool> {
+        let name = name.to_os_string();
+        self.run(move |handler| handler.text_exists(&name)).await
+    }
+
+    async fn rename_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        let (from, to) = (from.to_os_string(), to.to_os_string());
+        self.run(move |handler| handler.rename_text(&from, &to)).await
+    }
+
+    async fn copy_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        let (from, to) = (from.to_os_string(), to.to_os_string());
+        self.run(move |handler| handler.copy_text(&from, &to)).await
+    }
+
+    async fn list_texts(&self, prefix: &OsStr) -> IoResult<Vec<OsString>> {
+        let prefix = prefix.to_os_string();
+        self.run(move |handler| handler.list_texts(&prefix)).await
+    }
+
+    async fn create_dir_all(&mut self, name: &OsStr) -> IoResult<()> {
+        let name = name.to_os_string();
+        self.run(move |handler| handler.create_dir_all(&name)).await
+    }
+}
+
+/// AsyncToSyncAdapter makes an [`AsyncTextIOHandler`] usable as a [`TextIOHandler`],
+/// by running each call to completion on a private single-threaded tokio runtime.
+/// # Panics
+/// As tokio runtimes can't be nested, its methods panic when called from within an asynchronous context.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use string_io_and_mock::{AsyncMockTextHandler, AsyncToSyncAdapter, TextIOHandler};
+///
+/// let mut handler = AsyncToSyncAdapter::new(AsyncMockTextHandler::new()).unwrap();
+///
+/// handler.write_text(OsStr::new("a.txt"), String::from("alpha")).unwrap();
+/// assert_eq!("alpha", handler.read_text(OsStr::new("a.txt")).unwrap());
+/// ```
+pub struct AsyncToSyncAdapter<H> {
+    handler: H,
+    runtime: Runtime,
+}
+
+impl<H: AsyncTextIOHandler> AsyncToSyncAdapter<H> {
+    pub fn new(handler: H) -> IoResult<Self> {
+        let runtime = RuntimeBuilder::new_current_thread().enable_all().build()?;
+
+        Ok(AsyncToSyncAdapter {
+            handler,
+            runtime,
+        })
+    }
+
+    /// Returns the wrapped asynchronous handler.
+    pub fn into_inner(self) -> H {
+        self.handler
+    }
+}
+
+impl<H: AsyncTextIOHandler> TextIOHandler for AsyncToSyncAdapter<H> {
+
+    fn read_text(&self, name: &OsStr) -> IoResult<String> {
+        self.runtime.block_on(self.handler.read_text(name))
+    }
+
+    fn write_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        self.runtime.block_on(self.handler.write_text(name, content))
+    }
+
+    fn append_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        self.runtime.block_on(self.handler.append_text(name, content))
+    }
+
+    fn delete_text(&mut self, name: &OsStr) -> IoResult<()> {
+        self.runtime.block_on(self.handler.delete_text(name))
+    }
+
+    fn text_exists(&self, name: &OsStr) -> IoResult<bool> {
+        self.runtime.block_on(self.handler.text_exists(name))
+    }
+
+    fn rename_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        self.runtime.block_on(self.handler.rename_text(from, to))
+    }
+
+    fn copy_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        self.runtime.block_on(self.handler.copy_text(from, to))
+    }
+
+    fn list_texts(&self, prefix: &OsStr) -> IoResult<Vec<OsString>> {
+        self.runtime.block_on(self.handler.list_texts(prefix))
+    }
+
+    fn glob_texts(&self, pattern: &str) -> IoResult<Vec<OsString>> {
+        self.runtime.block_on(self.handler.glob_texts(pattern))
+    }
+
+    fn create_dir_all(&mut self, name: &OsStr) -> IoResult<()> {
+        self.runtime.block_on(self.handler.create_dir_all(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FailureRule;
+
+    #[tokio::test]
+    async fn mock_mirrors_sync_mock() {
+        let mut mock = AsyncMockTextHandler::new();
+        mock.write_text(OsStr::new("docs/a.md"), String::from("alpha")).await.unwrap();
+        mock.append_text(OsStr::new("docs/a.md"), String::from("bet")).await.unwrap();
+        mock.copy_text(OsStr::new("docs/a.md"), OsStr::new("docs/b.md")).await.unwrap();
+        mock.rename_text(OsStr::new("docs/b.md"), OsStr::new("c.md")).await.unwrap();
+
+        assert_eq!("alphabet", mock.read_text(OsStr::new("c.md")).await.unwrap());
+        assert_eq!(vec!["docs/a.md"], mock.glob_texts("docs/*.md").await.unwrap());
+
+        mock.delete_text(OsStr::new("c.md")).await.unwrap();
+        assert!(!mock.text_exists(OsStr::new("c.md")).await.unwrap());
+
+        let err = mock.delete_text(OsStr::new("c.md")).await.unwrap_err();
+        assert_eq!(ErrorKind::NotFound, err.kind());
+    }
+
+    #[tokio::test]
+    async fn mock_failure_injection() {
+        let mut mock = AsyncMockTextHandler::new();
+        mock.with_mock(|inner| inner.inject_failure(FailureRule::new(ErrorKind::StorageFull)));
+
+        let err = mock.write_text(OsStr::new("a.txt"), String::new()).await.unwrap_err();
+        assert_eq!(ErrorKind::StorageFull, err.kind());
+    }
+
+    #[tokio::test]
+    async fn sync_to_async() {
+        let mut handler = SyncToAsyncAdapter::new(MockTextHandler::new());
+        handler.write_text(OsStr::new("a.txt"), String::from("alpha")).await.unwrap();
+
+        assert_eq!("alpha", handler.read_text(OsStr::new("a.txt")).await.unwrap());
+        assert_eq!(vec!["a.txt"], handler.list_texts(OsStr::new("")).await.unwrap());
+    }
+
+    #[test]
+    fn async_to_sync() {
+        let mock = AsyncMockTextHandler::new();
+        let mut handler = AsyncToSyncAdapter::new(mock.clone()).unwrap();
+        handler.write_text(OsStr::new("a.txt"), String::from("alpha")).unwrap();
+
+        assert_eq!("alpha", handler.read_text(OsStr::new("a.txt")).unwrap());
+        assert_eq!(1, mock.with_mock(|inner| inner.writes_to("a.txt").len()));
+    }
+}
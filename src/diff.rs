@@ -0,0 +1,441 @@
+//! Comparison of the texts held by two handlers, or by a [`MockTextHandler`] and a [`MockSnapshot`].
+
+use std::collections::{BTreeSet, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::Result as IoResult;
+use crate::{BytesIOHandler, MockSnapshot, MockTextHandler, TextIOHandler};
+
+/// The number of unchanged lines shown around the changed lines of a modified text.
+const CONTEXT_LINES: usize = 3;
+
+/// The number of edits after which the search for a shortest script of line edits settles for a longer one.
+const MAX_SEARCH_EDITS: isize = 1024;
+
+/// A TextDiff lists the texts that were added, removed and modified between two states, sorted by name.
+/// For each modified text, it holds a unified diff of its lines, or a note if either content isn't valid UTF-8.
+///
+/// Formatting a TextDiff yields a readable report, fit for test failure messages.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use string_io_and_mock::{MockTextHandler, TextIOHandler};
+///
+/// let mut mock = MockTextHandler::new()
+///     .with_text("config.toml", "[server]\nport = 80\n")
+///     .with_text("data.csv", "a;b\n");
+/// let before = mock.snapshot();
+///
+/// // The migration under test :
+/// mock.write_text(OsStr::new("config.toml"), String::from("[server]\nport = 8080\n")).unwrap();
+///
+/// let diff = mock.diff_since(&before);
+/// assert_eq!(vec!["config.toml"], diff.changed_names(), "{}", diff);
+/// assert_eq!(
+///     "--- config.toml\n+++ config.toml\n@@ -1,2 +1,2 @@\n [server]\n-port = 80\n+port = 8080\n",
+///     diff.to_string());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextDiff {
+    pub added: Vec<OsString>,
+    pub removed: Vec<OsString>,
+    /// The names of the modified texts, with the diffs of their contents.
+    pub modified: Vec<(OsString, String)>,
+}
+
+impl TextDiff {
+    /// Returns whether both states hold the same texts.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    /// Returns the names of all texts added, removed or modified, sorted.
+    pub fn changed_names(&self) -> Vec<&OsStr> {
+        let mut names: Vec<&OsStr> = self.added
+            .iter()
+            .chain(self.removed.iter())
+            .chain(self.modified.iter().map(|(name, _)| name))
+            .map(OsString::as_os_str)
+            .collect();
+
+        names.sort();
+        names
+    }
+
+    /// Adds the difference between the old and new content of a text, if any.
+    fn compare(&mut self, name: &OsStr, old: Option<&[u8]>, new: Option<&[u8]>) {
+        match (old, new) {
+            (None, Some(_)) => self.added.push(name.to_os_string()),
+            (Some(_), None) => self.removed.push(name.to_os_string()),
+            (Some(old), Some(new)) if old != new => {
+                let diff = match (std::str::from_utf8(old), std::str::from_utf8(new)) {
+                    (Ok(old), Ok(new)) => unified_diff(old, new),
+                    _ => String::from("Binary contents differ.\n"),
+                };
+
+                self.modified.push((name.to_os_string(), diff));
+            },
+            _ => (),
+        }
+    }
+}
+
+impl Display for TextDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for name in &self.added {
+            writeln!(f, "Added: {}", name.to_string_lossy())?;
+        }
+
+        for name in &self.removed {
+            writeln!(f, "Removed: {}", name.to_string_lossy())?;
+        }
+
+        for (name, diff) in &self.modified {
+            let name = name.to_string_lossy();
+            write!(f, "--- {}\n+++ {}\n{}", name, name, diff)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares the texts at or below `prefix` held by two handlers,
+/// e.g. a handler's state before and after running the code under test,
+/// or the output of the code under test and the expected output.
+/// See [`TextIOHandler::list_texts`] for the meaning of the prefix.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use string_io_and_mock::{diff_handlers, MockTextHandler};
+///
+/// let expected = MockTextHandler::from_txtar("-- out/a.txt --\nalpha\n-- out/b.txt --\nbeta\n");
+/// let actual = MockTextHandler::from_txtar("-- out/a.txt --\nalpha\n-- out/c.txt --\ngamma\n");
+///
+/// let diff = diff_handlers(&expected, &actual, OsStr::new("out")).unwrap();
+/// assert_eq!(vec!["out/c.txt"], diff.added);
+/// assert_eq!(vec!["out/b.txt"], diff.removed);
+/// assert!(diff.modified.is_empty());
+/// ```
+pub fn diff_handlers<O, N>(old: &O, new: &N, prefix: &OsStr) -> IoResult<TextDiff>
+where
+    O: TextIOHandler + BytesIOHandler,
+    N: TextIOHandler + BytesIOHandler,
+{
+    let old_names: BTreeSet<OsString> = old.list_texts(prefix)?.into_iter().collect();
+    let new_names: BTreeSet<OsString> = new.list_texts(prefix)?.into_iter().collect();
+    let mut diff = TextDiff::default();
+
+    for name in old_names.union(&new_names) {
+        let old_content = match old_names.contains(name) {
+            true => Some(old.read_bytes(name)?),
+            false => None,
+        };
+
+        let new_content = match new_names.contains(name) {
+            true => Some(new.read_bytes(name)?),
+            false => None,
+        };
+
+        diff.compare(name, old_content.as_deref(), new_content.as_deref());
+    }
+
+    Ok(diff)
+}
+
+impl MockTextHandler {
+    /// Compares the texts stored by the mock with those of a snapshot taken earlier.
+    /// Comparing isn't recorded as calls to the mock.
+    pub fn diff_since(&self, snapshot: &MockSnapshot) -> TextDiff {
+        let names: BTreeSet<&OsString> = snapshot.texts.keys().chain(self.texts.keys()).collect();
+        let mut diff = TextDiff::default();
+
+        for name in names {
+            let old = snapshot.texts.get(name).map(|content| content.as_slice());
+            let new = self.texts.get(name).map(|content| content.as_slice());
+
+            diff.compare(name, old, new);
+        }
+
+        diff
+    }
+}
+
+enum Edit<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Returns the hunks of a unified diff between two texts, based on a shortest script of line edits.
+fn unified_diff(old: &str, new: &str) -> String {
+    let edits = line_edits(old, new);
+    let changes: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, edit)| !matches!(edit, Edit::Same(_)))
+        .map(|(index, _)| index)
+        .collect();
+
+    // Changes closer to each other than twice the context share a hunk.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+
+    for index in changes {
+        let start = index.saturating_sub(CONTEXT_LINES);
+        let end = (index + CONTEXT_LINES + 1).min(edits.len());
+
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut diff = String::new();
+
+    for (start, end) in hunks {
+        let old_start = edits[..start].iter().filter(|edit| !matches!(edit, Edit::Added(_))).count();
+        let new_start = edits[..start].iter().filter(|edit| !matches!(edit, Edit::Removed(_))).count();
+        let old_len = edits[start..end].iter().filter(|edit| !matches!(edit, Edit::Added(_))).count();
+        let new_len = edits[start..end].iter().filter(|edit| !matches!(edit, Edit::Removed(_))).count();
+
+        diff.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_len),
+            hunk_range(new_start, new_len)));
+
+        for edit in &edits[start..end] {
+            let (prefix, line) = match edit {
+                Edit::Same(line) => (' ', line),
+                Edit::Removed(line) => ('-', line),
+                Edit::Added(line) => ('+', line),
+            };
+
+            diff.push(prefix);
+            diff.push_str(line);
+
+            if !line.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    diff
+}
+
+/// Formats the range of a hunk, whose start is 1-based unless the range is empty.
+fn hunk_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        _ => format!("{},{}", start + 1, len),
+    }
+}
+
+/// Returns the edits turning the lines of `old` into those of `new`,
+/// with the removed lines of each change before the added ones.
+fn line_edits<'a>(old: &'a str, new: &'a str) -> Vec<Edit<'a>> {
+    let old: Vec<&str> = old.split_inclusive('\n').collect();
+    let new: Vec<&str> = new.split_inclusive('\n').collect();
+
+    // Lines the other text lacks can't be common, so they're left out of the search for a shortest script,
+    // which they would only slow down.
+    let in_old: HashSet<&str> = old.iter().copied().collect();
+    let in_new: HashSet<&str> = new.iter().copied().collect();
+    let old_shared: Vec<&str> = old.iter().copied().filter(|line| in_new.contains(line)).collect();
+    let new_shared: Vec<&str> = new.iter().copied().filter(|line| in_old.contains(line)).collect();
+
+    let mut script = Vec::new();
+    diff_lines(&old_shared, &new_shared, &mut script);
+
+    let mut edits = Vec::with_capacity(old.len() + new.len());
+    let mut added = Vec::new();
+    let mut old_lines = old.into_iter().peekable();
+    let mut new_lines = new.into_iter().peekable();
+
+    for edit in script.into_iter().map(Some).chain([None]) {
+        // The lines left out go before the next line of their text in the script.
+        while let Some(line) = old_lines.next_if(|line| !in_new.contains(line)) {
+            edits.push(Edit::Removed(line));
+        }
+
+        while let Some(line) = new_lines.next_if(|line| !in_old.contains(line)) {
+            added.push(Edit::Added(line));
+        }
+
+        match edit {
+            Some(Edit::Same(line)) => {
+                old_lines.next();
+                new_lines.next();
+                edits.append(&mut added);
+                edits.push(Edit::Same(line));
+            },
+            Some(Edit::Removed(line)) => {
+                old_lines.next();
+                edits.push(Edit::Removed(line));
+            },
+            Some(Edit::Added(line)) => {
+                new_lines.next();
+                added.push(Edit::Added(line));
+            },
+            None => edits.append(&mut added),
+        }
+    }
+
+    edits
+}
+
+/// Adds a shortest script of edits turning `old` into `new` to `script`, using Myers' linear space algorithm :
+/// once the common prefix and suffix are trimmed, the lines are split where a shortest script is halfway,
+/// and both parts are compared in turn.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str], script: &mut Vec<Edit<'a>>) {
+    let prefix = common_len(old.iter(), new.iter());
+    let suffix = common_len(old[prefix..].iter().rev(), new[prefix..].iter().rev());
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    script.extend(old[..prefix].iter().map(|line| Edit::Same(line)));
+
+    if old_middle.is_empty() || new_middle.is_empty() {
+        script.extend(old_middle.iter().map(|line| Edit::Removed(line)));
+        script.extend(new_middle.iter().map(|line| Edit::Added(line)));
+    } else {
+        let (x, y) = middle_split(old_middle, new_middle);
+        diff_lines(&old_middle[..x], &new_middle[..y], script);
+        diff_lines(&old_middle[x..], &new_middle[y..], script);
+    }
+
+    script.extend(old[old.len() - suffix..].iter().map(|line| Edit::Same(line)));
+}
+
+/// Returns the positions in `old` and `new` where a shortest edit script is halfway,
+/// by searching for shortest scripts from the start and from the end at the same time, until they meet.
+/// Search `d` tracks, per diagonal `k = x - y`, how far `d` edits reach from either end.
+///
+/// Like GNU diff, the search gives up after [`MAX_SEARCH_EDITS`] edits and splits at the furthest position
+/// reached from the start, so that texts with few lines in common, like a text and its lines reversed,
+/// don't take quadratic time, at the cost of a longer script.
+fn middle_split(old: &[&str], new: &[&str]) -> (usize, usize) {
+    let (n, m) = (old.len(), new.len());
+    let delta = n as isize - m as isize;
+    let odd = delta % 2 != 0;
+    let max = (n + m).div_ceil(2) + 1;
+    let at = |k: isize| (k + max as isize) as usize;
+
+    // The furthest x reached on each diagonal, counting from the start and from the end respectively.
+    let mut forward = vec![0; 2 * max + 1];
+    let mut backward = vec![0; 2 * max + 1];
+
+    for d in 0..max as isize {
+        for k in (-d..=d).rev().step_by(2) {
+            let x = match k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                true => forward[at(k + 1)],
+                false => forward[at(k - 1)] + 1,
+            };
+            let y = x as isize - k;
+            forward[at(k)] = x + common_from(old, new, x, y);
+
+            if odd && (k - delta).abs() < d && forward[at(k)] + backward[at(delta - k)] >= n {
+                return (x, y as usize);
+            }
+        }
+
+        if d >= MAX_SEARCH_EDITS {
+            let furthest = (-d..=d)
+                .step_by(2)
+                .map(|k| (forward[at(k)], forward[at(k)] as isize - k))
+                .filter(|&(x, y)| x <= n && (0..=m as isize).contains(&y) && x + (y as usize) < n + m)
+                .max_by_key(|&(x, y)| x + y as usize);
+
+            if let Some((x, y)) = furthest {
+                return (x, y as usize);
+            }
+        }
+
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = match k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                true => backward[at(k + 1)],
+                false => backward[at(k - 1)] + 1,
+            };
+            let common = common_before(old, new, x, x as isize - k);
+            x += common;
+            backward[at(k)] = x;
+
+            if !odd && (k - delta).abs() <= d && backward[at(k)] + forward[at(delta - k)] >= n {
+                return (n - x, (m as isize - (x as isize - k)) as usize);
+            }
+        }
+    }
+
+    unreachable!("both searches meet within (n + m) / 2 edits")
+}
+
+/// Returns the number of equal lines at the start of `old[x..]` and `new[y..]`.
+fn common_from(old: &[&str], new: &[&str], x: usize, y: isize) -> usize {
+    match usize::try_from(y) {
+        Ok(y) if x < old.len() && y < new.len() => common_len(old[x..].iter(), new[y..].iter()),
+        _ => 0,
+    }
+}
+
+/// Returns the number of equal lines at the end of `old` and `new` without their last `x` and `y` lines.
+fn common_before(old: &[&str], new: &[&str], x: usize, y: isize) -> usize {
+    match usize::try_from(y) {
+        Ok(y) if x < old.len() && y < new.len() => {
+            common_len(old[..old.len() - x].iter().rev(), new[..new.len() - y].iter().rev())
+        },
+        _ => 0,
+    }
+}
+
+fn common_len<T: PartialEq>(first: impl Iterator<Item = T>, second: impl Iterator<Item = T>) -> usize {
+    first.zip(second).take_while(|(first, second)| first == second).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hunks() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n14\n15\n16\n";
+        let new = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n14\n15\n";
+
+        assert_eq!(
+            "@@ -1,6 +1,6 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n@@ -13,4 +13,3 @@\n 13\n 14\n 15\n-16\n",
+            unified_diff(old, new));
+
+        assert_eq!("@@ -0,0 +1,1 @@\n+a\n\\ No newline at end of file\n", unified_diff("", "a"));
+        assert_eq!("@@ -1,2 +1,3 @@\n a\n+b\n c\n", unified_diff("a\nc\n", "a\nb\nc\n"));
+        assert_eq!("@@ -1,4 +1,4 @@\n-a\n b\n-c\n+x\n d\n+y\n", unified_diff("a\nb\nc\nd\n", "b\nx\nd\ny\n"));
+    }
+
+    #[test]
+    fn long_texts() {
+        let old: String = (0..20000).map(|line| format!("{}\n", line)).collect();
+        let new = old.replace("\n10000\n", "\n10000!\n");
+
+        assert_eq!(
+            "@@ -9998,7 +9998,7 @@\n 9997\n 9998\n 9999\n-10000\n+10000!\n 10001\n 10002\n 10003\n",
+            unified_diff(&old, &new));
+    }
+
+    #[test]
+    fn diff_since_snapshot() {
+        let mut mock = MockTextHandler::new()
+            .with_text("a.txt", "alpha\n")
+            .with_text("b.txt", "beta\n")
+            .with_bytes("c.bin", vec![0xff]);
+        let snapshot = mock.snapshot();
+        assert!(mock.diff_since(&snapshot).is_empty());
+
+        mock.delete_text(OsStr::new("a.txt")).unwrap();
+        mock.write_text(OsStr::new("b.txt"), String::from("BETA\n")).unwrap();
+        mock.write_bytes(OsStr::new("c.bin"), vec![0xfe]).unwrap();
+        mock.write_text(OsStr::new("d.txt"), String::new()).unwrap();
+
+        let diff = mock.diff_since(&snapshot);
+        assert_eq!(vec!["a.txt", "b.txt", "c.bin", "d.txt"], diff.changed_names());
+        assert_eq!(
+            "Added: d.txt\nRemoved: a.txt\n--- b.txt\n+++ b.txt\n@@ -1,1 +1,1 @@\n-beta\n+BETA\n\
+                --- c.bin\n+++ c.bin\nBinary contents differ.\n",
+            diff.to_string());
+    }
+}
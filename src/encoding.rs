@@ -0,0 +1,415 @@
+//! Character encoding support on top of a [`TextIOHandler`] that also implements [`BytesIOHandler`].
+
+use std::ffi::{OsStr, OsString};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use crate::error::{annotate, reannotate};
+use crate::{BytesIOHandler, TextIOHandler, TextOperation};
+
+const UTF8_BOM: &[u8] = &[0xef, 0xbb, 0xbf];
+const UTF16LE_BOM: &[u8] = &[0xff, 0xfe];
+const UTF16BE_BOM: &[u8] = &[0xfe, 0xff];
+
+/// The characters of Windows-1252 in the range 0x80 to 0x9F, where it differs from Latin-1.
+/// The five bytes Windows-1252 leaves undefined map to the control characters with the same code.
+const WINDOWS1252_HIGH: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}',
+];
+
+/// The character encodings supported by [`EncodingTextHandler`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// ISO-8859-1, mapping each byte to the Unicode character with the same code.
+    Latin1,
+    Windows1252,
+}
+
+impl Encoding {
+    /// Returns the encoding whose byte order mark `content` starts with, and the length of that mark.
+    fn detect(content: &[u8]) -> Option<(Encoding, usize)> {
+        [(Encoding::Utf8, UTF8_BOM), (Encoding::Utf16Le, UTF16LE_BOM), (Encoding::Utf16Be, UTF16BE_BOM)]
+            .into_iter()
+            .find(|(_, bom)| content.starts_with(bom))
+            .map(|(encoding, bom)| (encoding, bom.len()))
+    }
+
+    fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 => UTF8_BOM,
+            Encoding::Utf16Le => UTF16LE_BOM,
+            Encoding::Utf16Be => UTF16BE_BOM,
+            Encoding::Latin1 | Encoding::Windows1252 => &[],
+        }
+    }
+
+    fn decode(self, content: Vec<u8>) -> IoResult<String> {
+        match self {
+            Encoding::Utf8 => String::from_utf8(content)
+                .map_err(|_| IoError::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8")),
+            Encoding::Utf16Le => decode_utf16(&content, u16::from_le_bytes),
+            Encoding::Utf16Be => decode_utf16(&content, u16::from_be_bytes),
+            Encoding::Latin1 => Ok(content.into_iter().map(char::from).collect()),
+            Encoding::Windows1252 => Ok(content
+                .into_iter()
+                .map(|byte| match byte {
+                    0x80..=0x9f => WINDOWS1252_HIGH[usize::from(byte - 0x80)],
+                    _ => char::from(byte),
+                })
+                .collect()),
+        }
+    }
+
+    fn encode(self, content: &str) -> IoResult<Vec<u8>> {
+        match self {
+            Encoding::Utf8 => Ok(content.as_bytes().to_vec()),
+            Encoding::Utf16Le => Ok(content.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+            Encoding::Utf16Be => Ok(content.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+            Encoding::Latin1 => content
+                .chars()
+                .map(|c| u8::try_from(c).map_err(|_| unencodable(c, self)))
+                .collect(),
+            Encoding::Windows1252 => content
+                .chars()
+                .map(|c| match WINDOWS1252_HIGH.iter().position(|high| *high == c) {
+                    Some(index) => Ok(0x80 + index as u8),
+                    None => match u8::try_from(c) {
+                        Ok(byte) if !(0x80..=0x9f).contains(&byte) => Ok(byte),
+                        _ => Err(unencodable(c, self)),
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+fn decode_utf16(content: &[u8], to_unit: fn([u8; 2]) -> u16) -> IoResult<String> {
+    let invalid = || IoError::new(ErrorKind::InvalidData, "stream did not contain valid UTF-16");
+
+    if !content.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+
+    let units = content.chunks_exact(2).map(|pair| to_unit([pair[0], pair[1]]));
+
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|_| invalid())
+}
+
+fn unencodable(c: char, encoding: Encoding) -> IoError {
+    IoError::new(ErrorKind::InvalidInput, format!("character {:?} can't be encoded in {:?}", c, encoding))
+}
+
+/// Tells whether [`EncodingTextHandler`] writes a byte order mark in front of the texts it writes.
+/// Latin-1 and Windows-1252 have no byte order mark, so texts in these encodings are always written without one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BomPolicy {
+    #[default]
+    Never,
+    Always,
+}
+
+/// EncodingTextHandler reads and writes texts in other encodings than UTF-8,
+/// using any handler implementing both [`TextIOHandler`] and [`BytesIOHandler`] to store them.
+///
+/// When reading, a UTF-8, UTF-16LE or UTF-16BE byte order mark is detected and stripped off.
+/// Content without byte order mark is decoded using the fallback encoding, which defaults to UTF-8.
+/// Content that can't be decoded makes `read_text` fail with [`ErrorKind::InvalidData`].
+///
+/// When writing, texts are encoded using the write encoding, which defaults to UTF-8,
+/// preceded by a byte order mark if the [`BomPolicy`] says so.
+/// Texts containing characters the write encoding can't represent make `write_text`
+/// fail with [`ErrorKind::InvalidInput`].
+/// As the encoding of an existing text must be respected, `append_text` encodes the content
+/// appended like the text : in the encoding of its byte order mark, which is kept,
+/// or else in the fallback encoding. Appending to a missing or empty text is like writing it.
+/// Either way, the whole text is written back.
+///
+/// The other methods are passed on to the wrapped handler.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use string_io_and_mock::{
+///     BomPolicy, BytesIOHandler, Encoding, EncodingTextHandler, MockTextHandler, TextIOHandler,
+/// };
+///
+/// let mock = MockTextHandler::new()
+///     .with_bytes("utf16.csv", vec![0xff, 0xfe, b'a', 0, b';', 0, b'b', 0])
+///     .with_bytes("ansi.ini", b"price=5\x80".to_vec());
+///
+/// let mut handler = EncodingTextHandler::new(mock)
+///     .with_fallback(Encoding::Windows1252)
+///     .with_write_encoding(Encoding::Utf16Be)
+///     .with_bom_policy(BomPolicy::Always);
+///
+/// assert_eq!("a;b", handler.read_text(OsStr::new("utf16.csv")).unwrap());
+/// assert_eq!("price=5\u{20ac}", handler.read_text(OsStr::new("ansi.ini")).unwrap());
+///
+/// handler.write_text(OsStr::new("out.txt"), String::from("ok")).unwrap();
+/// assert_eq!(
+///     vec![0xfe, 0xff, 0, b'o', 0, b'k'],
+///     handler.into_inner().read_bytes(OsStr::new("out.txt")).unwrap());
+/// ```
+pub struct EncodingTextHandler<H> {
+    handler: H,
+    fallback: Encoding,
+    write_encoding: Encoding,
+    bom_policy: BomPolicy,
+}
+
+impl<H: TextIOHandler + BytesIOHandler> EncodingTextHandler<H> {
+    pub fn new(handler: H) -> Self {
+        EncodingTextHandler {
+            handler,
+            fallback: Encoding::default(),
+            write_encoding: Encoding::default(),
+            bom_policy: BomPolicy::default(),
+        }
+    }
+
+    /// Sets the encoding used to decode content without byte order mark.
+    pub fn with_fallback(mut self, encoding: Encoding) -> Self {
+        self.fallback = encoding;
+        self
+    }
+
+    /// Sets the encoding used to write texts.
+    pub fn with_write_encoding(mut self, encoding: Encoding) -> Self {
+        self.write_encoding = encoding;
+        self
+    }
+
+    /// Sets whether texts are written with a byte order mark.
+    pub fn with_bom_policy(mut self, bom_policy: BomPolicy) -> Self {
+        self.bom_policy = bom_policy;
+        self
+    }
+
+    /// Returns the wrapped handler.
+    pub fn into_inner(self) -> H {
+        self.handler
+    }
+
+    fn decode(&self, mut content: Vec<u8>) -> IoResult<String> {
+        match Encoding::detect(&content) {
+            Some((encoding, bom_len)) => {
+                content.drain(..bom_len);
+                encoding.decode(content)
+            },
+            None => self.fallback.decode(content),
+        }
+    }
+
+    /// Returns the existing content of a text with the given content appended,
+    /// encoded like the existing content.
+    fn append_encoded(&self, name: &OsStr, content: &str) -> IoResult<Vec<u8>> {
+        let mut existing = match self.handler.text_exists(name)? {
+            true => self.handler.read_bytes(name)?,
+            false => Vec::new(),
+        };
+
+        let appended = match Encoding::detect(&existing) {
+            Some((encoding, _)) => encoding.encode(content)?,
+            None if existing.is_empty() => self.encode(content)?,
+            None => self.fallback.encode(content)?,
+        };
+
+        existing.extend(appended);
+
+        Ok(existing)
+    }
+
+    fn encode(&self, content: &str) -> IoResult<Vec<u8>> {
+        let mut encoded = match self.bom_policy {
+            BomPolicy::Never => Vec::new(),
+            BomPolicy::Always => self.write_encoding.bom().to_vec(),
+        };
+
+        encoded.extend(self.write_encoding.encode(content)?);
+
+        Ok(encoded)
+    }
+}
+
+impl<H: TextIOHandler + BytesIOHandler> TextIOHandler for EncodingTextHandler<H> {
+
+    fn read_text(&self, name: &OsStr) -> IoResult<String> {
+        let result = self.handler.read_bytes(name).and_then(|content| self.decode(content));
+
+        reannotate(result, TextOperation::Read, name, None)
+    }
+
+    fn write_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        let encoded = annotate(self.encode(&content), TextOperation::Write, name, None)?;
+
+        reannotate(self.handler.write_bytes(name, encoded), TextOperation::Write, name, None)
+    }
+
+    fn append_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        let result = self.append_encoded(name, &content)
+            .and_then(|encoded| self.handler.write_bytes(name, encoded));
+
+        reannotate(result, TextOperation::Append, name, None)
+    }
+
+    fn delete_text(&mut self, name: &OsStr) -> IoResult<()> {
+        self.handler.delete_text(name)
+    }
+
+    fn text_exists(&self, name: &OsStr) -> IoResult<bool> {
+        self.handler.text_exists(name)
+    }
+
+    fn rename_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        self.handler.rename_text(from, to)
+    }
+
+    fn copy_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        self.handler.copy_text(from, to)
+    }
+
+    fn list_texts(&self, prefix: &OsStr) -> IoResult<Vec<OsString>> {
+        self.handler.list_texts(prefix)
+    }
+
+    fn glob_texts(&self, pattern: &str) -> IoResult<Vec<OsString>> {
+        self.handler.glob_texts(pattern)
+    }
+
+    fn create_dir_all(&mut self, name: &OsStr) -> IoResult<()> {
+        self.handler.create_dir_all(name)
+    }
+
+    /// Compares the encoded content, so a text in another encoding is considered changed.
+    fn write_text_if_changed(&mut self, name: &OsStr, content: String) -> IoResult<bool> {
+        let encoded = annotate(self.encode(&content), TextOperation::Write, name, None)?;
+
+        if self.handler.read_bytes(name).is_ok_and(|existing| existing == encoded) {
+            return Ok(false);
+        }
+
+        reannotate(self.handler.write_bytes(name, encoded), TextOperation::Write, name, None).map(|_| true)
+    }
+
+    /// Decodes the content peeked at by the wrapped handler like `read_text` does,
+    /// or as lossy UTF-8 if it can't be decoded.
+    fn peek_text(&self, name: &OsStr) -> IoResult<Option<String>> {
+        let result = self.handler.peek_bytes(name).map(|content| content.map(|content| {
+            self.decode(content.clone())
+                .unwrap_or_else(|_| String::from_utf8_lossy(&content).into_owned())
+        }));
+
+        reannotate(result, TextOperation::Read, name, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FailureRule, MockTextHandler, TextIOError};
+
+    #[test]
+    fn decodes_by_bom() {
+        let handler = EncodingTextHandler::new(MockTextHandler::new()
+            .with_bytes("utf8.txt", b"\xef\xbb\xbfcaf\xc3\xa9".to_vec())
+            .with_bytes("utf16le.txt", vec![0xff, 0xfe, 0x3d, 0xd8, 0x00, 0xde])
+            .with_bytes("utf16be.txt", vec![0xfe, 0xff, 0x00, 0xe9])
+            .with_bytes("odd.txt", vec![0xfe, 0xff, 0x00]));
+
+        assert_eq!("caf\u{e9}", handler.read_text(OsStr::new("utf8.txt")).unwrap());
+        assert_eq!("\u{1f600}", handler.read_text(OsStr::new("utf16le.txt")).unwrap());
+        assert_eq!("\u{e9}", handler.read_text(OsStr::new("utf16be.txt")).unwrap());
+
+        let err = handler.read_text(OsStr::new("odd.txt")).unwrap_err();
+        assert_eq!(ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn decodes_by_fallback() {
+        let mock = MockTextHandler::new().with_bytes("ansi.txt", b"\x93caf\xe9\x94".to_vec());
+
+        let handler = EncodingTextHandler::new(mock);
+        let err = handler.read_text(OsStr::new("ansi.txt")).unwrap_err();
+        assert_eq!(ErrorKind::InvalidData, err.kind());
+
+        let handler = handler.with_fallback(Encoding::Latin1);
+        assert_eq!("\u{93}caf\u{e9}\u{94}", handler.read_text(OsStr::new("ansi.txt")).unwrap());
+
+        let handler = handler.with_fallback(Encoding::Windows1252);
+        assert_eq!("\u{201c}caf\u{e9}\u{201d}", handler.read_text(OsStr::new("ansi.txt")).unwrap());
+    }
+
+    #[test]
+    fn encodes() {
+        let mut handler = EncodingTextHandler::new(MockTextHandler::new())
+            .with_write_encoding(Encoding::Utf16Le)
+            .with_bom_policy(BomPolicy::Always);
+
+        handler.write_text(OsStr::new("a.txt"), String::from("a")).unwrap();
+        handler.append_text(OsStr::new("a.txt"), String::from("b")).unwrap();
+
+        let mut handler = handler.with_write_encoding(Encoding::Latin1);
+        handler.write_text(OsStr::new("b.txt"), String::from("caf\u{e9}")).unwrap();
+
+        let err = handler.write_text(OsStr::new("c.txt"), String::from("\u{20ac}")).unwrap_err();
+        assert_eq!(ErrorKind::InvalidInput, err.kind());
+
+        let mut handler = handler.with_write_encoding(Encoding::Windows1252);
+        handler.write_text(OsStr::new("c.txt"), String::from("\u{20ac}")).unwrap();
+
+        let err = handler.write_text(OsStr::new("d.txt"), String::from("\u{80}")).unwrap_err();
+        assert_eq!(ErrorKind::InvalidInput, err.kind());
+
+        let mock = handler.into_inner();
+        assert_eq!(vec![0xff, 0xfe, b'a', 0, b'b', 0], mock.read_bytes(OsStr::new("a.txt")).unwrap());
+        assert_eq!(b"caf\xe9".to_vec(), mock.read_bytes(OsStr::new("b.txt")).unwrap());
+        assert_eq!(vec![0x80], mock.read_bytes(OsStr::new("c.txt")).unwrap());
+        assert!(!mock.text_exists(OsStr::new("d.txt")).unwrap());
+    }
+
+    #[test]
+    fn appends_in_existing_encoding() {
+        let mock = MockTextHandler::new()
+            .with_bytes("utf16be.txt", vec![0xfe, 0xff, 0, b'a'])
+            .with_bytes("ansi.txt", b"caf\xe9".to_vec())
+            .with_bytes("empty.txt", Vec::new());
+
+        let mut handler = EncodingTextHandler::new(mock)
+            .with_fallback(Encoding::Windows1252)
+            .with_bom_policy(BomPolicy::Always);
+
+        handler.append_text(OsStr::new("utf16be.txt"), String::from("b")).unwrap();
+        handler.append_text(OsStr::new("ansi.txt"), String::from("\u{20ac}")).unwrap();
+        handler.append_text(OsStr::new("empty.txt"), String::from("a")).unwrap();
+        handler.append_text(OsStr::new("new.txt"), String::from("a")).unwrap();
+
+        let mock = handler.into_inner();
+        assert_eq!(vec![0xfe, 0xff, 0, b'a', 0, b'b'], mock.read_bytes(OsStr::new("utf16be.txt")).unwrap());
+        assert_eq!(b"caf\xe9\x80".to_vec(), mock.read_bytes(OsStr::new("ansi.txt")).unwrap());
+        assert_eq!(b"\xef\xbb\xbfa".to_vec(), mock.read_bytes(OsStr::new("empty.txt")).unwrap());
+        assert_eq!(b"\xef\xbb\xbfa".to_vec(), mock.read_bytes(OsStr::new("new.txt")).unwrap());
+    }
+
+    #[test]
+    fn errors_name_handler_operations() {
+        let mut mock = MockTextHandler::new();
+        mock.inject_failure(FailureRule::new(ErrorKind::StorageFull).on(TextOperation::WriteBytes));
+        let mut handler = EncodingTextHandler::new(mock);
+
+        let err = handler.write_text(OsStr::new("a"), String::from("alpha")).unwrap_err();
+        assert_eq!(ErrorKind::StorageFull, err.kind());
+        assert_eq!("write_text of \"a\" failed: injected failure", err.to_string());
+
+        let err = handler.append_text(OsStr::new("a"), String::from("alpha")).unwrap_err();
+        assert_eq!(TextOperation::Append, TextIOError::find(&err).unwrap().operation());
+
+        let err = handler.read_text(OsStr::new("a")).unwrap_err();
+        assert_eq!("read_text of \"a\" failed: entity not found", err.to_string());
+    }
+}
@@ -0,0 +1,125 @@
+//! The error type describing failed [`TextIOHandler`](crate::TextIOHandler) operations.
+
+use std::error::Error;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use crate::TextOperation;
+
+/// TextIOError describes which operation failed on which text, and why.
+///
+/// For compatibility, the [`TextIOHandler`](crate::TextIOHandler) methods still return
+/// a [`std::io::Error`], but the implementors in this crate wrap a TextIOError in it,
+/// keeping the [`ErrorKind`] of the underlying error.
+/// Method [`TextIOError::find`] retrieves it.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use std::io::ErrorKind;
+/// use string_io_and_mock::{MockTextHandler, TextIOError, TextIOHandler, TextOperation};
+///
+/// let mock = MockTextHandler::new();
+/// let err = mock.read_text(OsStr::new("config.toml")).unwrap_err();
+///
+/// assert_eq!(ErrorKind::NotFound, err.kind());
+/// assert_eq!("read_text of \"config.toml\" failed: entity not found", err.to_string());
+///
+/// let text_err = TextIOError::find(&err).unwrap();
+/// assert_eq!(TextOperation::Read, text_err.operation());
+/// assert_eq!(OsStr::new("config.toml"), text_err.name());
+/// ```
+#[derive(Debug)]
+pub struct TextIOError {
+    operation: TextOperation,
+    name: OsString,
+    target: Option<OsString>,
+    source: IoError,
+}
+
+impl TextIOError {
+    pub fn new(operation: TextOperation, name: &OsStr, target: Option<&OsStr>, source: IoError) -> Self {
+        TextIOError {
+            operation,
+            name: name.to_os_string(),
+            target: target.map(OsStr::to_os_string),
+            source,
+        }
+    }
+
+    /// Returns the TextIOError wrapped in an error returned by a handler, if any.
+    pub fn find(err: &IoError) -> Option<&TextIOError> {
+        err.get_ref()?.downcast_ref::<TextIOError>()
+    }
+
+    /// The operation that failed.
+    pub fn operation(&self) -> TextOperation {
+        self.operation
+    }
+
+    /// The name of the text the operation applied to.
+    /// For `list_texts`, this is the prefix. For `rename_text` and `copy_text`, this is the source name.
+    pub fn name(&self) -> &OsStr {
+        &self.name
+    }
+
+    /// The destination name of a failed `rename_text` or `copy_text`.
+    pub fn target(&self) -> Option<&OsStr> {
+        self.target.as_deref()
+    }
+
+    /// The kind of the underlying error.
+    pub fn kind(&self) -> ErrorKind {
+        self.source.kind()
+    }
+}
+
+impl Display for TextIOError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} of {:?}", self.operation, self.name)?;
+
+        if let Some(target) = &self.target {
+            write!(f, " to {:?}", target)?;
+        }
+
+        write!(f, " failed: {}", self.source)
+    }
+}
+
+impl Error for TextIOError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<TextIOError> for IoError {
+    fn from(err: TextIOError) -> Self {
+        IoError::new(err.kind(), err)
+    }
+}
+
+/// Wraps the error of a failed operation in a [`TextIOError`], unless it already is one.
+pub(crate) fn annotate<T>(result: IoResult<T>, operation: TextOperation, name: &OsStr, target: Option<&OsStr>) -> IoResult<T> {
+    result.map_err(|err| match TextIOError::find(&err) {
+        Some(_) => err,
+        None => TextIOError::new(operation, name, target, err).into(),
+    })
+}
+
+/// Wraps the error of a failed operation in a [`TextIOError`], replacing the one it may already be,
+/// e.g. when a handler performs an operation by calling other operations of the handler it wraps.
+pub(crate) fn reannotate<T>(result: IoResult<T>, operation: TextOperation, name: &OsStr, target: Option<&OsStr>) -> IoResult<T> {
+    annotate(result.map_err(unwrap_annotation), operation, name, target)
+}
+
+/// Returns the underlying error of an error wrapping a [`TextIOError`], or the error itself.
+fn unwrap_annotation(err: IoError) -> IoError {
+    if TextIOError::find(&err).is_none() {
+        return err;
+    }
+
+    match err.into_inner().map(|inner| inner.downcast::<TextIOError>()) {
+        Some(Ok(text_err)) => text_err.source,
+        Some(Err(inner)) => IoError::other(inner),
+        None => unreachable!("an error wrapping a TextIOError has an inner error"),
+    }
+}
@@ -0,0 +1,134 @@
+//! Expectations for strict verification of [`MockTextHandler`](crate::MockTextHandler) usage.
+
+use std::cell::Cell;
+use std::ffi::{OsStr, OsString};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use crate::{TextCall, TextOperation};
+
+/// An Expectation describes calls a [`MockTextHandler`](crate::MockTextHandler) should receive.
+/// Expectations are created using the mock's `expect`, `expect_read`, `expect_write`
+/// and `expect_append` methods and configured using the builder methods below.
+///
+/// A call is accepted by the first expectation with the call's operation and name
+/// (the source name for `rename_text` and `copy_text`) that hasn't reached its number of calls yet.
+/// Calls that aren't accepted by any expectation access the stored texts as usual,
+/// unless the mock is strict, in which case they make the mock panic.
+///
+/// All expectations are verified when the mock is dropped, or by calling its `verify` method.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use string_io_and_mock::{MockTextHandler, TextIOHandler};
+///
+/// let mut mock = MockTextHandler::new();
+/// mock.set_strict(true);
+/// mock.expect_write("a.txt").with_content("alpha").times(1);
+/// mock.expect_read("b.txt").returning(Ok(String::from("beta")));
+///
+/// // The code under test :
+/// let input = mock.read_text(OsStr::new("b.txt")).unwrap();
+/// assert_eq!("beta", input);
+/// mock.write_text(OsStr::new("a.txt"), String::from("alpha")).unwrap();
+///
+/// // Any other call would have panicked, and dropping the mock panics
+/// // if "a.txt" wasn't written exactly once.
+/// ```
+#[derive(Debug)]
+pub struct Expectation {
+    operation: TextOperation,
+    name: OsString,
+    content: Option<String>,
+    times: Option<usize>,
+    result: Option<Result<String, (ErrorKind, String)>>,
+    calls: Cell<usize>,
+}
+
+impl Expectation {
+    pub(crate) fn new(operation: TextOperation, name: &OsStr) -> Self {
+        Expectation {
+            operation,
+            name: name.to_os_string(),
+            content: None,
+            times: None,
+            result: None,
+            calls: Cell::new(0),
+        }
+    }
+
+    /// Restricts the expectation to `write_text` or `append_text` calls passing the given content.
+    /// # Panics
+    /// Panics if the expectation is for another operation, as its calls pass no content to match.
+    pub fn with_content<C: Into<String>>(&mut self, content: C) -> &mut Self {
+        assert!(
+            matches!(self.operation, TextOperation::Write | TextOperation::Append),
+            "Content can only be expected for write_text and append_text, not for {}.",
+            self.operation);
+
+        self.content = Some(content.into());
+        self
+    }
+
+    /// Requires the expectation to accept exactly `n` calls.
+    /// Without this, any number of calls is accepted, including none.
+    pub fn times(&mut self, n: usize) -> &mut Self {
+        self.times = Some(n);
+        self
+    }
+
+    /// Requires the expectation not to be called at all.
+    pub fn never(&mut self) -> &mut Self {
+        self.times(0)
+    }
+
+    /// Makes accepted calls return the given result without accessing the stored texts.
+    /// Calls to other methods than `read_text` ignore the content of an `Ok` result.
+    pub fn returning(&mut self, result: IoResult<String>) -> &mut Self {
+        self.result = Some(result.map_err(|err| (err.kind(), err.to_string())));
+        self
+    }
+
+    /// Returns whether the expectation covers the call, comparing names by the keys `key` returns for them.
+    pub(crate) fn matches(&self, call: &TextCall, key: impl Fn(&OsStr) -> OsString) -> bool {
+        self.operation == call.operation
+            && key(&self.name) == key(&call.name)
+            && (self.content.is_none() || self.content == call.content)
+    }
+
+    pub(crate) fn register_call(&self) {
+        self.calls.set(self.calls.get() + 1);
+    }
+
+    pub(crate) fn result(&self) -> Option<IoResult<String>> {
+        self.result.as_ref().map(|result| match result {
+            Ok(content) => Ok(content.clone()),
+            Err((kind, message)) => Err(IoError::new(*kind, message.clone())),
+        })
+    }
+
+    pub(crate) fn is_saturated(&self) -> bool {
+        self.times.is_some_and(|times| self.calls.get() >= times)
+    }
+
+    pub(crate) fn is_exceeded(&self) -> bool {
+        self.times.is_some_and(|times| self.calls.get() > times)
+    }
+
+    pub(crate) fn is_met(&self) -> bool {
+        self.times.is_none_or(|times| self.calls.get() == times)
+    }
+
+    pub(crate) fn describe_failure(&self) -> String {
+        let content = match &self.content {
+            None => String::new(),
+            Some(content) => format!(" with content {:?}", content),
+        };
+
+        format!(
+            "expected {} call(s) to {} of {:?}{}, got {}",
+            self.times.unwrap_or_default(),
+            self.operation,
+            self.name,
+            content,
+            self.calls.get())
+    }
+}
@@ -0,0 +1,134 @@
+//! Failure injection for [`MockTextHandler`](crate::MockTextHandler).
+
+use std::ffi::{OsStr, OsString};
+use std::io::{Error as IoError, ErrorKind};
+use crate::{glob_match, TextOperation};
+
+/// A FailureRule makes a [`MockTextHandler`](crate::MockTextHandler) return an error
+/// instead of performing an operation, so that the error paths of code using the mock can be tested.
+///
+/// By default, a rule applies to every call of every operation on any name.
+/// Its builder methods narrow this down to given operations, a given name or name pattern,
+/// or a single call.
+/// For operations involving two names, i.e. `rename_text` and `copy_text`,
+/// a rule applies if either of the names matches.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use std::io::ErrorKind;
+/// use string_io_and_mock::{FailureRule, MockTextHandler, TextIOHandler, TextOperation};
+///
+/// let mut mock = MockTextHandler::new();
+/// mock.inject_failure(
+///     FailureRule::new(ErrorKind::StorageFull)
+///         .on(TextOperation::Write)
+///         .for_pattern("config/*.toml")
+///         .on_nth_call(3));
+///
+/// let name = OsStr::new("config/app.toml");
+/// mock.write_text(name, String::from("a = 1")).unwrap();
+/// mock.write_text(name, String::from("a = 2")).unwrap();
+///
+/// let err = mock.write_text(name, String::from("a = 3")).unwrap_err();
+/// assert_eq!(ErrorKind::StorageFull, err.kind());
+///
+/// // The failing write didn't change the stored text, and later writes succeed again.
+/// assert_eq!("a = 2", mock.read_text(name).unwrap());
+/// mock.write_text(name, String::from("a = 4")).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct FailureRule {
+    kind: ErrorKind,
+    operations: Vec<TextOperation>,
+    target: Target,
+    nth_call: Option<usize>,
+    matching_calls: usize,
+}
+
+#[derive(Clone, Debug)]
+enum Target {
+    Any,
+    Name(OsString),
+    Pattern(String),
+}
+
+impl FailureRule {
+    /// Creates a rule that makes every call fail with an error of the given kind.
+    pub fn new(kind: ErrorKind) -> Self {
+        FailureRule {
+            kind,
+            operations: Vec::new(),
+            target: Target::Any,
+            nth_call: None,
+            matching_calls: 0,
+        }
+    }
+
+    /// Restricts the rule to the given operation.
+    /// Calling this method several times makes the rule apply to each of the operations given.
+    pub fn on(mut self, operation: TextOperation) -> Self {
+        self.operations.push(operation);
+        self
+    }
+
+    /// Restricts the rule to calls on the given name.
+    pub fn for_name<N: AsRef<OsStr>>(mut self, name: N) -> Self {
+        self.target = Target::Name(name.as_ref().to_os_string());
+        self
+    }
+
+    /// Restricts the rule to calls on names matching the given glob pattern.
+    /// See [`glob_match`] for the supported syntax.
+    pub fn for_pattern(mut self, pattern: &str) -> Self {
+        self.target = Target::Pattern(pattern.to_string());
+        self
+    }
+
+    /// Makes the rule fail only the `n`th call (counting from 1)
+    /// that matches its operations and name restrictions.
+    pub fn on_nth_call(mut self, n: usize) -> Self {
+        self.nth_call = Some(n);
+        self
+    }
+
+    /// Makes the rule fail every call that matches its operations and name restrictions.
+    /// This is the default.
+    pub fn on_every_call(mut self) -> Self {
+        self.nth_call = None;
+        self
+    }
+
+    /// Registers a call and returns the error to be returned for it, if any.
+    /// Names are compared, and matched against the pattern, by the keys `key` returns for them.
+    pub(crate) fn check(
+        &mut self,
+        operation: TextOperation,
+        names: &[&OsStr],
+        key: impl Fn(&OsStr) -> OsString,
+    ) -> Option<IoError> {
+        if !self.applies_to(operation, names, key) {
+            return None;
+        }
+
+        self.matching_calls += 1;
+
+        match self.nth_call {
+            Some(n) if n != self.matching_calls => None,
+            _ => Some(IoError::new(self.kind, "injected failure")),
+        }
+    }
+
+    fn applies_to(&self, operation: TextOperation, names: &[&OsStr], key: impl Fn(&OsStr) -> OsString) -> bool {
+        if !self.operations.is_empty() && !self.operations.contains(&operation) {
+            return false;
+        }
+
+        match &self.target {
+            Target::Any => true,
+            Target::Name(target) => names.iter().any(|name| key(name) == key(target)),
+            Target::Pattern(pattern) => names
+                .iter()
+                .any(|name| key(name).to_str().is_some_and(|name| glob_match(pattern, name))),
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Loading a [`MockTextHandler`] from a fixture directory, and exporting its texts to a directory.
+
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::path::{Component, Path, PathBuf};
+use crate::{glob_match, BytesIOHandler, FileTextHandler, MockTextHandler, TextIOHandler};
+
+/// A FixtureLoader reads all files below a directory into a new [`MockTextHandler`],
+/// so that tests can use a realistic set of texts without accessing the file system afterwards.
+///
+/// By default, the texts are stored under their names relative to the directory.
+/// Builder methods allow storing them under absolute names instead,
+/// and selecting the files to load using glob patterns matched against the relative names.
+/// See [`glob_match`] for the supported syntax.
+///
+/// The files are read using a [`FileTextHandler`] confined to the directory,
+/// so symbolic links leading outside of the directory make loading fail.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use std::fs::{create_dir_all, write};
+/// use string_io_and_mock::{FixtureLoader, TextIOHandler};
+///
+/// create_dir_all("tests/playground/fixture/docs").unwrap();
+/// write("tests/playground/fixture/docs/intro.md", "# Intro").unwrap();
+/// write("tests/playground/fixture/docs/draft.md", "# Draft").unwrap();
+/// write("tests/playground/fixture/build.log", "ok").unwrap();
+///
+/// let mock = FixtureLoader::new("tests/playground/fixture")
+///     .include("**/*.md")
+///     .exclude("**/draft.md")
+///     .load()
+///     .unwrap();
+///
+/// assert_eq!(vec!["docs/intro.md"], mock.list_texts(OsStr::new("")).unwrap());
+/// assert_eq!("# Intro", mock.read_text(OsStr::new("docs/intro.md")).unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct FixtureLoader {
+    dir: PathBuf,
+    absolute_keys: bool,
+    includes: Vec<String>,
+    excludes: Vec<String>,
+}
+
+impl FixtureLoader {
+    /// Creates a loader for all files below the directory `dir`.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        FixtureLoader {
+            dir: dir.into(),
+            absolute_keys: false,
+            includes: Vec::new(),
+            excludes: Vec::new(),
+        }
+    }
+
+    /// Makes the loader store the texts under their absolute, canonical file names,
+    /// instead of their names relative to the directory.
+    pub fn with_absolute_keys(mut self, absolute_keys: bool) -> Self {
+        self.absolute_keys = absolute_keys;
+        self
+    }
+
+    /// Restricts loading to the files whose relative names match the given glob pattern.
+    /// Calling this method several times loads the files matching any of the patterns given.
+    pub fn include(mut self, pattern: &str) -> Self {
+        self.includes.push(pattern.to_string());
+        self
+    }
+
+    /// Skips the files whose relative names match the given glob pattern,
+    /// even if they match an included pattern.
+    pub fn exclude(mut self, pattern: &str) -> Self {
+        self.excludes.push(pattern.to_string());
+        self
+    }
+
+    /// Reads the selected files into a new [`MockTextHandler`].
+    /// Loading isn't recorded as calls to the mock.
+    /// Fails with [`ErrorKind::NotFound`] if the directory doesn't exist.
+    pub fn load(&self) -> IoResult<MockTextHandler> {
+        let fth = FileTextHandler::new().with_root(&self.dir);
+        let base = match self.absolute_keys {
+            true => self.dir.canonicalize()?,
+            false => PathBuf::new(),
+        };
+
+        let mut mock = MockTextHandler::new();
+
+        for name in fth.list_texts(OsStr::new(""))? {
+            if self.selects(&name) {
+                let content = fth.read_bytes(&name)?;
+                mock = mock.with_bytes(base.join(&name), content);
+            }
+        }
+
+        Ok(mock)
+    }
+
+    fn selects(&self, name: &OsStr) -> bool {
+        let Some(name) = Path::new(name).to_str() else {
+            return self.includes.is_empty();
+        };
+
+        (self.includes.is_empty() || self.includes.iter().any(|pattern| glob_match(pattern, name)))
+            && !self.excludes.iter().any(|pattern| glob_match(pattern, name))
+    }
+}
+
+impl MockTextHandler {
+    /// Creates a mock holding all files below the directory `dir`, under their names relative to `dir`.
+    /// Use a [`FixtureLoader`] for more options.
+    pub fn from_dir<P: Into<PathBuf>>(dir: P) -> IoResult<Self> {
+        FixtureLoader::new(dir).load()
+    }
+
+    /// Writes all texts of the mock to files below the directory `dir`, creating `dir`
+    /// and the subdirectories needed, so that they can be inspected using the usual tools.
+    /// In file system emulation mode, the mock's directories are created as well.
+    ///
+    /// Texts with absolute names are written below `dir` as if their names were relative.
+    /// If two texts would be written to the same file, e.g. `/a.txt` and `a.txt`, the export fails
+    /// with [`ErrorKind::AlreadyExists`] before writing anything.
+    /// Texts with names escaping `dir` using `..` components make the export fail
+    /// with [`ErrorKind::PermissionDenied`].
+    ///
+    /// Files already present in `dir` are left in place, or overwritten by texts with the same name,
+    /// so exporting to a fresh or emptied directory is advisable.
+    /// Exporting isn't recorded as calls to the mock.
+    /// # Examples
+    /// ```
+    /// use std::ffi::OsStr;
+    /// use std::fs::read_to_string;
+    /// use string_io_and_mock::{MockTextHandler, TextIOHandler};
+    ///
+    /// let mut mock = MockTextHandler::new();
+    /// mock.write_text(OsStr::new("out/report.txt"), String::from("Done.")).unwrap();
+    ///
+    /// mock.export_to_dir("tests/playground/export").unwrap();
+    /// assert_eq!("Done.", read_to_string("tests/playground/export/out/report.txt").unwrap());
+    /// ```
+    pub fn export_to_dir<P: AsRef<Path>>(&self, dir: P) -> IoResult<()> {
+        let mut names: Vec<&OsString> = self.texts.keys().collect();
+        names.sort();
+
+        let mut files: BTreeMap<PathBuf, &OsString> = BTreeMap::new();
+
+        for name in names {
+            let file = relative_name(name);
+
+            if let Some(other) = files.get(&file) {
+                return Err(IoError::new(
+                    ErrorKind::AlreadyExists,
+                    format!("texts {:?} and {:?} would both be exported to {:?}", other, name, file)));
+            }
+
+            files.insert(file, name);
+        }
+
+        let dir = dir.as_ref();
+        FileTextHandler::new().create_dir_all(dir.as_os_str())?;
+
+        let mut fth = FileTextHandler::new().with_root(dir).with_parent_dirs(true);
+
+        let mut dirs: Vec<&OsString> = self.dirs.iter().collect();
+        dirs.sort();
+
+        for name in dirs {
+            fth.create_dir_all(relative_name(name).as_os_str())?;
+        }
+
+        for (file, name) in files {
+            fth.write_bytes(file.as_os_str(), self.texts[name].to_vec())?;
+        }
+
+        Ok(())
+    }
+}
+
+fn relative_name(name: &OsStr) -> PathBuf {
+    Path::new(name)
+        .components()
+        .filter(|component| !matches!(component, Component::Prefix(_) | Component::RootDir))
+        .collect()
+}
@@ -0,0 +1,176 @@
+//! Implementations of [`TextIOHandler`] and [`BytesIOHandler`] for references, boxes and shared handlers,
+//! so that handlers can be passed by reference, used as trait objects or shared
+//! without adapter code.
+
+use std::cell::RefCell;
+use std::ffi::{OsStr, OsString};
+use std::io::{Error as IoError, Result as IoResult};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, MutexGuard};
+use crate::{BytesIOHandler, TextIOHandler};
+
+/// Implements the [`TextIOHandler`] methods by forwarding them to another handler.
+/// `$shared` and `$exclusive` are expressions in terms of `$this` yielding something
+/// that dereferences to the handler, for the methods taking `&self` and `&mut self` respectively.
+macro_rules! forward_text_io_handler {
+    ($this:ident => $shared:expr, $exclusive:expr) => {
+        fn read_text(&$this, name: &OsStr) -> IoResult<String> {
+            $shared.read_text(name)
+        }
+
+        fn write_text(&mut $this, name: &OsStr, content: String) -> IoResult<()> {
+            $exclusive.write_text(name, content)
+        }
+
+        fn append_text(&mut $this, name: &OsStr, content: String) -> IoResult<()> {
+            $exclusive.append_text(name, content)
+        }
+
+        fn delete_text(&mut $this, name: &OsStr) -> IoResult<()> {
+            $exclusive.delete_text(name)
+        }
+
+        fn text_exists(&$this, name: &OsStr) -> IoResult<bool> {
+            $shared.text_exists(name)
+        }
+
+        fn rename_text(&mut $this, from: &OsStr, to: &OsStr) -> IoResult<()> {
+            $exclusive.rename_text(from, to)
+        }
+
+        fn copy_text(&mut $this, from: &OsStr, to: &OsStr) -> IoResult<()> {
+            $exclusive.copy_text(from, to)
+        }
+
+        fn list_texts(&$this, prefix: &OsStr) -> IoResult<Vec<OsString>> {
+            $shared.list_texts(prefix)
+        }
+
+        fn glob_texts(&$this, pattern: &str) -> IoResult<Vec<OsString>> {
+            $shared.glob_texts(pattern)
+        }
+
+        fn create_dir_all(&mut $this, name: &OsStr) -> IoResult<()> {
+            $exclusive.create_dir_all(name)
+        }
+
+        fn write_text_if_changed(&mut $this, name: &OsStr, content: String) -> IoResult<bool> {
+            $exclusive.write_text_if_changed(name, content)
+        }
+
+        fn peek_text(&$this, name: &OsStr) -> IoResult<Option<String>> {
+            $shared.peek_text(name)
+        }
+    };
+}
+
+/// Implements the [`BytesIOHandler`] methods by forwarding them to another handler,
+/// like `forward_text_io_handler` does.
+macro_rules! forward_bytes_io_handler {
+    ($this:ident => $shared:expr, $exclusive:expr) => {
+        fn read_bytes(&$this, name: &OsStr) -> IoResult<Vec<u8>> {
+            $shared.read_bytes(name)
+        }
+
+        fn write_bytes(&mut $this, name: &OsStr, content: Vec<u8>) -> IoResult<()> {
+            $exclusive.write_bytes(name, content)
+        }
+
+        fn read_text_lossy(&$this, name: &OsStr) -> IoResult<String> {
+            $shared.read_text_lossy(name)
+        }
+
+        fn peek_bytes(&$this, name: &OsStr) -> IoResult<Option<Vec<u8>>> {
+            $shared.peek_bytes(name)
+        }
+    };
+}
+
+impl<T: TextIOHandler + ?Sized> TextIOHandler for &mut T {
+    forward_text_io_handler!(self => (**self), (**self));
+}
+
+impl<T: TextIOHandler + ?Sized> TextIOHandler for Box<T> {
+    forward_text_io_handler!(self => (**self), (**self));
+}
+
+/// # Panics
+/// The methods panic if the handler is already mutably borrowed,
+/// or if a mutating method is called while the handler is borrowed.
+impl<T: TextIOHandler + ?Sized> TextIOHandler for Rc<RefCell<T>> {
+    forward_text_io_handler!(self => self.borrow(), self.borrow_mut());
+}
+
+/// The methods return an error of kind [`std::io::ErrorKind::Other`] if the mutex is poisoned.
+impl<T: TextIOHandler + ?Sized> TextIOHandler for Arc<Mutex<T>> {
+    forward_text_io_handler!(self => lock_handler(self)?, lock_handler(self)?);
+}
+
+impl<T: BytesIOHandler + ?Sized> BytesIOHandler for &mut T {
+    forward_bytes_io_handler!(self => (**self), (**self));
+}
+
+impl<T: BytesIOHandler + ?Sized> BytesIOHandler for Box<T> {
+    forward_bytes_io_handler!(self => (**self), (**self));
+}
+
+impl<T: BytesIOHandler + ?Sized> BytesIOHandler for Rc<RefCell<T>> {
+    forward_bytes_io_handler!(self => self.borrow(), self.borrow_mut());
+}
+
+impl<T: BytesIOHandler + ?Sized> BytesIOHandler for Arc<Mutex<T>> {
+    forward_bytes_io_handler!(self => lock_handler(self)?, lock_handler(self)?);
+}
+
+fn lock_handler<T: ?Sized>(handler: &Mutex<T>) -> IoResult<MutexGuard<'_, T>> {
+    handler
+        .lock()
+        .map_err(|_| IoError::other("the mutex guarding the text handler is poisoned"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTextHandler;
+
+    fn store<H: TextIOHandler>(mut handler: H) {
+        handler.write_text(OsStr::new("a.txt"), String::from("alpha")).unwrap();
+    }
+
+    #[test]
+    fn mutable_reference() {
+        let mut mock = MockTextHandler::new();
+        store(&mut mock);
+
+        assert_eq!("alpha", mock.read_text(OsStr::new("a.txt")).unwrap());
+    }
+
+    #[test]
+    fn boxed_trait_object() {
+        let mut handler: Box<dyn TextIOHandler> = Box::new(MockTextHandler::new());
+        store(&mut handler);
+
+        assert_eq!("alpha", handler.read_text(OsStr::new("a.txt")).unwrap());
+        assert_eq!(vec!["a.txt"], handler.glob_texts("*.txt").unwrap());
+    }
+
+    #[test]
+    fn shared_handlers() {
+        let local = Rc::new(RefCell::new(MockTextHandler::new()));
+        store(Rc::clone(&local));
+        assert_eq!("alpha", local.read_text(OsStr::new("a.txt")).unwrap());
+
+        let threaded = Arc::new(Mutex::new(MockTextHandler::new()));
+        store(Arc::clone(&threaded));
+        assert_eq!("alpha", threaded.read_text(OsStr::new("a.txt")).unwrap());
+    }
+
+    #[test]
+    fn bytes_handlers() {
+        let mut handler: Box<dyn BytesIOHandler + Send> = Box::new(MockTextHandler::new());
+        handler.write_bytes(OsStr::new("a.bin"), vec![1, 2, 3]).unwrap();
+
+        let shared = Arc::new(Mutex::new(handler));
+        assert_eq!(vec![1, 2, 3], shared.read_bytes(OsStr::new("a.bin")).unwrap());
+    }
+}
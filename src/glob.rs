@@ -0,0 +1,234 @@
+//! Glob matching of text names, as used by [`TextIOHandler::glob_texts`](crate::TextIOHandler::glob_texts).
+
+/// Returns whether `name` matches the glob `pattern`.
+///
+/// Both the pattern and the name are split into path components on `/` (and on `\` on Windows).
+/// Within a component, the following wildcards are supported :
+/// - `*` matches any sequence of characters;
+/// - `?` matches any single character;
+/// - `[abc]`, `[a-z]` and `[!a-z]` (or `[^a-z]`) match a single character in or not in a set.
+///
+/// A component consisting of `**` matches zero or more whole components.
+///
+/// A rooted pattern, starting with a separator, only matches rooted names, and vice versa.
+/// # Examples
+/// ```
+/// use string_io_and_mock::glob_match;
+///
+/// assert!(glob_match("docs/*.md", "docs/intro.md"));
+/// assert!(!glob_match("docs/*.md", "docs/api/intro.md"));
+/// assert!(glob_match("docs/**/*.md", "docs/api/intro.md"));
+/// assert!(!glob_match("/docs/*.md", "docs/intro.md"));
+/// ```
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    if pattern.starts_with(is_separator) != name.starts_with(is_separator) {
+        return false;
+    }
+
+    let pattern_parts = split_components(pattern);
+    let name_parts = split_components(name);
+
+    match_components(&pattern_parts, &name_parts)
+}
+
+/// Returns the leading components of `pattern` that contain no wildcards, joined by `/`.
+/// The prefix of an absolute pattern starts with `/`.
+pub(crate) fn literal_prefix(pattern: &str) -> String {
+    let prefix = split_components(pattern)
+        .into_iter()
+        .take_while(|part| !part.contains(['*', '?', '[']))
+        .collect::<Vec<&str>>()
+        .join("/");
+
+    match pattern.starts_with(is_separator) {
+        true => format!("/{}", prefix),
+        false => prefix,
+    }
+}
+
+/// Returns the components of `pattern` following its [`literal_prefix`].
+pub(crate) fn wildcard_components(pattern: &str) -> Vec<&str> {
+    split_components(pattern)
+        .into_iter()
+        .skip_while(|part| !part.contains(['*', '?', '[']))
+        .collect()
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || (cfg!(windows) && c == '\\')
+}
+
+fn split_components(path: &str) -> Vec<&str> {
+    path.split(is_separator)
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect()
+}
+
+// Both matchers below backtrack only to the most recent `**` or `*`, which keeps them in O(pattern × name):
+// a later star can absorb anything an earlier one would have had to retry.
+fn match_components(pattern: &[&str], name: &[&str]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut resume = None;
+
+    while n < name.len() {
+        if pattern.get(p) == Some(&"**") {
+            resume = Some((p, n));
+            p += 1;
+        } else if p < pattern.len() && match_component(pattern[p], name[n]) {
+            p += 1;
+            n += 1;
+        } else if let Some((star, skipped)) = resume {
+            resume = Some((star, skipped + 1));
+            p = star + 1;
+            n = skipped + 1;
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|part| *part == "**")
+}
+
+/// Returns whether the single path component `name` matches the pattern component `pattern`.
+pub(crate) fn match_component(pattern: &str, name: &str) -> bool {
+    let pattern_chars: Vec<char> = pattern.chars().collect();
+    let name_chars: Vec<char> = name.chars().collect();
+
+    match_chars(&pattern_chars, &name_chars)
+}
+
+fn match_chars(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut resume = None;
+
+    while n < name.len() {
+        if pattern.get(p) == Some(&'*') {
+            resume = Some((p, n));
+            p += 1;
+        } else if let Some(consumed) = match_char(&pattern[p..], name[n]) {
+            p += consumed;
+            n += 1;
+        } else if let Some((star, skipped)) = resume {
+            resume = Some((star, skipped + 1));
+            p = star + 1;
+            n = skipped + 1;
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Matches `c` against the single-character item at the start of `pattern`.
+/// Returns the number of pattern characters consumed if it matches.
+fn match_char(pattern: &[char], c: char) -> Option<usize> {
+    match pattern.first()? {
+        '?' => Some(1),
+        '[' => match match_class(&pattern[1..]) {
+            Some((matcher, class_len)) => matcher(c).then_some(1 + class_len),
+
+            // An unterminated class is taken literally.
+            None => (c == '[').then_some(1),
+        },
+        literal => (*literal == c).then_some(1),
+    }
+}
+
+/// Parses a character class following its opening `[`.
+/// Returns a predicate and the number of pattern characters consumed, including the closing `]`.
+fn match_class(pattern: &[char]) -> Option<(impl Fn(char) -> bool, usize)> {
+    let negated = matches!(pattern.first(), Some('!') | Some('^'));
+    let start = if negated { 1 } else { 0 };
+
+    // A ']' directly after the opening bracket (and optional negation) is part of the set.
+    let close = pattern
+        .iter()
+        .enumerate()
+        .skip(start + 1)
+        .find(|(_, c)| **c == ']')
+        .map(|(index, _)| index)?;
+
+    let mut ranges = Vec::new();
+    let members = &pattern[start..close];
+    let mut index = 0;
+
+    while index < members.len() {
+        if index + 2 < members.len() && members[index + 1] == '-' {
+            ranges.push((members[index], members[index + 2]));
+            index += 3;
+        } else {
+            ranges.push((members[index], members[index]));
+            index += 1;
+        }
+    }
+
+    let matcher = move |c: char| ranges.iter().any(|(low, high)| *low <= c && c <= *high) != negated;
+
+    Some((matcher, close + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards() {
+        assert!(glob_match("*.md", "README.md"));
+        assert!(!glob_match("*.md", "README.txt"));
+        assert!(glob_match("file?.txt", "file1.txt"));
+        assert!(!glob_match("file?.txt", "file10.txt"));
+        assert!(glob_match("file[0-9].txt", "file7.txt"));
+        assert!(!glob_match("file[!0-9].txt", "file7.txt"));
+        assert!(glob_match("file[]].txt", "file].txt"));
+        assert!(glob_match("file[.txt", "file[.txt"));
+    }
+
+    #[test]
+    fn components() {
+        assert!(glob_match("config/*.toml", "config/app.toml"));
+        assert!(!glob_match("config/*.toml", "config/env/app.toml"));
+        assert!(!glob_match("*", "config/app.toml"));
+        assert!(glob_match("./config//app.toml", "config/app.toml"));
+        assert!(glob_match("/etc/*.toml", "/etc/app.toml"));
+        assert!(!glob_match("/etc/*.toml", "etc/app.toml"));
+        assert!(!glob_match("etc/*.toml", "/etc/app.toml"));
+        assert!(!glob_match("**/*.toml", "/etc/app.toml"));
+    }
+
+    #[test]
+    fn double_star() {
+        assert!(glob_match("**/*.md", "README.md"));
+        assert!(glob_match("**/*.md", "docs/api/intro.md"));
+        assert!(glob_match("docs/**", "docs/api/intro.md"));
+        assert!(glob_match("docs/**/intro.md", "docs/intro.md"));
+        assert!(!glob_match("docs/**/intro.md", "src/intro.md"));
+        assert!(glob_match("**/api/**/*.md", "docs/api/v1/api/intro.md"));
+    }
+
+    #[test]
+    fn pathological_patterns() {
+        let name = "a".repeat(100);
+        assert!(!glob_match(&format!("{}b", "*a".repeat(50)), &name));
+
+        let components = vec!["a"; 100].join("/");
+        assert!(!glob_match(&format!("{}/b", vec!["**/a"; 50].join("/")), &components));
+    }
+
+    #[test]
+    fn literal_prefixes() {
+        assert_eq!("docs/api", literal_prefix("docs/api/*.md"));
+        assert_eq!("", literal_prefix("**/*.md"));
+        assert_eq!("docs", literal_prefix("./docs/**"));
+        assert_eq!("/etc/app", literal_prefix("/etc/app/*.toml"));
+        assert_eq!("/", literal_prefix("/*.toml"));
+    }
+
+    #[test]
+    fn wildcard_components_follow_prefix() {
+        assert_eq!(vec!["*.md"], wildcard_components("docs/api/*.md"));
+        assert_eq!(vec!["**", "*.md"], wildcard_components("./**/*.md"));
+        assert_eq!(vec!["a*", "b"], wildcard_components("/etc/a*/b"));
+        assert!(wildcard_components("docs/intro.md").is_empty());
+    }
+}
@@ -3,8 +3,24 @@
 //! trait :
 //! - method `write_text` writes String content to a file or file system simulator;
 //! - method `read_text` reads String content from a file or file system simulator;
+//! - method `append_text` adds String content to the end of a text, creating it if it's missing;
+//! - method `delete_text` removes a text from a file system or file system simulator;
+//! - method `text_exists` tells whether a text with a given name is present;
+//! - method `rename_text` moves a text to a new name;
+//! - method `copy_text` copies a text to a new name;
+//! - method `list_texts` enumerates the names of the texts below a prefix;
+//! - method `glob_texts` enumerates the names of the texts matching a glob pattern;
+//! - method `create_dir_all` creates a directory and its missing parents;
+//! - method `write_text_if_changed` writes String content unless the text already has that content;
+//! - method `peek_text` reads a text for a handler wrapping another one, e.g. to detect its line endings.
 //!
 //! The *Text* in the names of the trait and structs mean that these entities are only meant to handle **`String`** content, as is evident from the signatures of the trait's methods.
+//! For binary content, both structs also implement the sibling trait [`BytesIOHandler`].
+//! Its `read_text_lossy` method reads texts that aren't valid UTF-8, replacing the invalid sequences,
+//! and its `peek_bytes` method is the byte content counterpart of `peek_text`.
+//! Texts in other encodings, like UTF-16 or Windows-1252, can be read and written
+//! by wrapping a handler in an [`EncodingTextHandler`].
+//! Similarly, a [`LineEndingTextHandler`] converts line endings according to a [`LineEndingPolicy`].
 //!
 //! For unit tests - or for other applications - a mock [`MockTextHandler`] is available that also
 //! implements the [`TextIOHandler`] trait, but doesn't access any file system. It stores it texts in
@@ -13,18 +29,248 @@
 //! This means that `MockTextHandler` is more than a mere mock: with its internal persistence, 
 //! it can serve as an application component in its own right,
 //! providing string storage in memory where file storage isn't needed.
+//! Where several components or threads need to share the same in-memory texts,
+//! a [`SharedMockTextHandler`] can be used.
+//!
+//! [`TextIOHandler`] is also implemented for `&mut T`, `Box<T>` (including `Box<dyn TextIOHandler>`),
+//! `Rc<RefCell<T>>` and `Arc<Mutex<T>>` where `T` implements it,
+//! so that handlers can be passed by reference, used as trait objects or shared.
+//!
+//! With the `async` feature, an `AsyncTextIOHandler` trait is available, implemented by
+//! the tokio-based `AsyncFileTextHandler` and by `AsyncMockTextHandler`.
+//! Adapters `SyncToAsyncAdapter` and `AsyncToSyncAdapter` convert between both traits.
+//!
+//! In order to test error paths, a `MockTextHandler` can be made to fail given operations
+//! using [`FailureRule`]s. It also records the calls made to it as [`TextCall`]s,
+//! so that tests can verify how their code uses the mock.
+//! For stricter verification, [`Expectation`]s can be set up on a `MockTextHandler`.
+//! A `MockTextHandler` can be filled with the files of a fixture directory using a [`FixtureLoader`],
+//! and its texts can be exported to a directory for inspection, e.g. when a test fails.
+//! Sets of texts can also be kept inline in a test as a [`Txtar`] archive.
+//! A mock can be rolled back to a [`MockSnapshot`] of its texts taken earlier,
+//! or compared to it, yielding a [`TextDiff`]. Function [`diff_handlers`] compares any two handlers.
+//!
+//! Both handlers report errors in the same way : the [`std::io::Error`]s they return
+//! wrap a [`TextIOError`] telling which operation failed on which text.
 
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::ffi::{OsString, OsStr};
-use std::io::{Error as IoError, ErrorKind, Result as IoResult};
-use std::fs::{read_to_string, write};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult, Write};
+use std::fs::{self, 
+    copy, metadata, read_dir, read_to_string, remove_file, rename, write, DirBuilder, File,
+    OpenOptions,
+};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use error::annotate;
+
+#[cfg(feature = "async")]
+mod async_io;
+mod diff;
+mod encoding;
+mod error;
+mod expectation;
+mod failure;
+mod fixtures;
+#[macro_use]
+mod forwarding;
+mod glob;
+mod line_endings;
+mod path;
+mod recording;
+mod shared;
+mod snapshot;
+mod txtar;
+
+#[cfg(feature = "async")]
+pub use async_io::{AsyncFileTextHandler, AsyncMockTextHandler, AsyncTextIOHandler, AsyncToSyncAdapter, SyncToAsyncAdapter};
+pub use diff::{diff_handlers, TextDiff};
+pub use encoding::{BomPolicy, Encoding, EncodingTextHandler};
+pub use error::TextIOError;
+pub use expectation::Expectation;
+pub use failure::FailureRule;
+pub use fixtures::FixtureLoader;
+pub use glob::glob_match;
+pub use line_endings::{LineEndingPolicy, LineEndingTextHandler};
+pub use recording::TextCall;
+pub use shared::SharedMockTextHandler;
+pub use snapshot::MockSnapshot;
+pub use txtar::Txtar;
 
 /// Implementors provide the ability to accept [`std::string::String`] content associated with an [`std::ffi::OsStr`] name, as can be expected from entities mediating a file system or their mocks and simulators.
+///
+/// Implementors should report the same [`ErrorKind`] values for the same conditions,
+/// e.g. [`ErrorKind::NotFound`] when the text to be read, deleted, renamed or copied is missing.
 pub trait TextIOHandler {
     fn read_text(&self, name: &OsStr) -> IoResult<String>;
     fn write_text(&mut self, name: &OsStr, content: String) -> IoResult<()>;
+
+    /// Adds `content` to the end of the text with the given name, creating the text if it's missing.
+    fn append_text(&mut self, name: &OsStr, content: String) -> IoResult<()>;
+
+    /// Removes the text with the given name.
+    fn delete_text(&mut self, name: &OsStr) -> IoResult<()>;
+
+    /// Returns whether a text with the given name is present.
+    fn text_exists(&self, name: &OsStr) -> IoResult<bool>;
+
+    /// Moves the text named `from` to the name `to`, replacing any text already present under `to`.
+    fn rename_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()>;
+
+    /// Copies the text named `from` to the name `to`, replacing any text already present under `to`.
+    /// Copying a text onto itself leaves it untouched.
+    fn copy_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()>;
+
+    /// Returns the names of all texts at or below `prefix`, sorted.
+    /// The prefix is matched per path component, so prefix `docs` covers `docs/intro.md`
+    /// but not `docs_old/intro.md`. An empty prefix covers all texts.
+    /// A prefix that covers no texts yields an empty list.
+    fn list_texts(&self, prefix: &OsStr) -> IoResult<Vec<OsString>>;
+
+    /// Returns the names of all texts matching the glob `pattern`, sorted.
+    /// See [`glob_match`] for the supported syntax.
+    fn glob_texts(&self, pattern: &str) -> IoResult<Vec<OsString>> {
+        let prefix = glob::literal_prefix(pattern);
+
+        Ok(self.list_texts(OsStr::new(&prefix))?
+            .into_iter()
+            .filter(|name| name.to_str().is_some_and(|name| glob_match(pattern, name)))
+            .collect())
+    }
+
+    /// Creates the directory with the given name and all of its missing parents.
+    fn create_dir_all(&mut self, name: &OsStr) -> IoResult<()>;
+
+    /// Writes `content` to the text with the given name, unless the text already has that content,
+    /// so as not to touch its modification time.
+    /// Returns whether the text was written.
+    /// # Examples
+    /// ```
+    /// use std::ffi::OsStr;
+    /// use string_io_and_mock::{MockTextHandler, TextIOHandler};
+    ///
+    /// let mut mock = MockTextHandler::new();
+    /// let name = OsStr::new("generated.rs");
+    ///
+    /// assert!(mock.write_text_if_changed(name, String::from("fn a() {}")).unwrap());
+    /// assert!(!mock.write_text_if_changed(name, String::from("fn a() {}")).unwrap());
+    /// assert!(mock.write_text_if_changed(name, String::from("fn b() {}")).unwrap());
+    ///
+    /// assert_eq!(2, mock.writes_to("generated.rs").len());
+    /// ```
+    fn write_text_if_changed(&mut self, name: &OsStr, content: String) -> IoResult<bool> {
+        if self.read_text(name).is_ok_and(|existing| existing == content) {
+            return Ok(false);
+        }
+
+        self.write_text(name, content).map(|_| true)
+    }
+
+    /// Reads the text with the given name on behalf of a handler wrapping this one,
+    /// which needs the existing content to adapt the content it writes, like [`LineEndingTextHandler`] does.
+    /// Returns `Ok(None)` if the text is missing.
+    ///
+    /// The handlers of this crate read content that isn't valid UTF-8 lossily, like `read_text_lossy`,
+    /// and [`MockTextHandler`] doesn't record the read, nor check it against expectations or failure rules,
+    /// as it isn't a read by the code under test.
+    fn peek_text(&self, name: &OsStr) -> IoResult<Option<String>> {
+        if_present(self.read_text(name))
+    }
+}
+
+/// Implementors provide the ability to accept raw byte content associated with an [`std::ffi::OsStr`] name,
+/// like [`TextIOHandler`] does for [`String`] content.
+///
+/// Implementors that also implement [`TextIOHandler`] store texts and byte contents alike :
+/// - a text written using `write_text` can be read using `read_bytes`, yielding its UTF-8 encoding;
+/// - byte content written using `write_bytes` can be read using `read_text` if it's valid UTF-8,
+///   otherwise `read_text` fails with [`ErrorKind::InvalidData`], as [`std::fs::read_to_string`] does;
+/// - the other [`TextIOHandler`] methods, like `append_text`, `rename_text` and `list_texts`,
+///   apply to byte contents as well;
+/// - `read_text_lossy` reads any content as a text, replacing invalid UTF-8 sequences.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use std::io::ErrorKind;
+/// use string_io_and_mock::{BytesIOHandler, MockTextHandler, TextIOHandler};
+///
+/// let mut mock = MockTextHandler::new();
+///
+/// mock.write_text(OsStr::new("greeting.txt"), String::from("hello")).unwrap();
+/// assert_eq!(b"hello".to_vec(), mock.read_bytes(OsStr::new("greeting.txt")).unwrap());
+///
+/// mock.write_bytes(OsStr::new("image.png"), vec![0x89, 0x50, 0x4e, 0x47]).unwrap();
+/// let err = mock.read_text(OsStr::new("image.png")).unwrap_err();
+/// assert_eq!(ErrorKind::InvalidData, err.kind());
+/// ```
+pub trait BytesIOHandler {
+    fn read_bytes(&self, name: &OsStr) -> IoResult<Vec<u8>>;
+    fn write_bytes(&mut self, name: &OsStr, content: Vec<u8>) -> IoResult<()>;
+
+    /// Reads content as a text, replacing invalid UTF-8 sequences by `U+FFFD REPLACEMENT CHARACTER`
+    /// instead of failing, like [`String::from_utf8_lossy`].
+    fn read_text_lossy(&self, name: &OsStr) -> IoResult<String> {
+        let content = self.read_bytes(name)?;
+
+        match String::from_utf8(content) {
+            Ok(text) => Ok(text),
+            Err(err) => Ok(String::from_utf8_lossy(err.as_bytes()).into_owned()),
+        }
+    }
+    /// Reads content on behalf of a handler wrapping this one, like [`TextIOHandler::peek_text`] does for texts,
+    /// e.g. for [`EncodingTextHandler`] to decode it.
+    /// Returns `Ok(None)` if the content is missing.
+    ///
+    /// [`MockTextHandler`] doesn't record the read, nor check it against expectations or failure rules.
+    fn peek_bytes(&self, name: &OsStr) -> IoResult<Option<Vec<u8>>> {
+        if_present(self.read_bytes(name))
+    }
 }
 
+/// The operations of the [`TextIOHandler`] and [`BytesIOHandler`] traits.
+/// Method `glob_texts` is covered by `List`, as it lists texts as well.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextOperation {
+    Read,
+    Write,
+    Append,
+    Delete,
+    Exists,
+    Rename,
+    Copy,
+    List,
+    CreateDir,
+    ReadBytes,
+    WriteBytes,
+}
+
+impl Display for TextOperation {
+    /// Writes the name of the [`TextIOHandler`] method performing the operation.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let method_name = match self {
+            TextOperation::Read => "read_text",
+            TextOperation::Write => "write_text",
+            TextOperation::Append => "append_text",
+            TextOperation::Delete => "delete_text",
+            TextOperation::Exists => "text_exists",
+            TextOperation::Rename => "rename_text",
+            TextOperation::Copy => "copy_text",
+            TextOperation::List => "list_texts",
+            TextOperation::CreateDir => "create_dir_all",
+            TextOperation::ReadBytes => "read_bytes",
+            TextOperation::WriteBytes => "write_bytes",
+        };
+
+        f.write_str(method_name)
+    }
+}
 
 /// FileTextHandler provides string read and write operations to file system files.
 /// It has no internal persistence, as this is provided by the underlying file system.
@@ -52,27 +298,489 @@ pub trait TextIOHandler {
 /// assert_eq!(content, read_back);
 /// ```
 #[derive(Default)]
-pub struct FileTextHandler {}
+pub struct FileTextHandler {
+    write_mode: WriteMode,
+    root: Option<PathBuf>,
+    parent_dirs: bool,
+    #[cfg(unix)]
+    dir_mode: Option<u32>,
+}
+
 impl FileTextHandler {
     pub fn new() -> Self {
-        FileTextHandler {}
+        FileTextHandler {
+            write_mode: WriteMode::Direct,
+            root: None,
+            parent_dirs: false,
+            #[cfg(unix)]
+            dir_mode: None,
+        }
+    }
+
+    /// Sets the way method `write_text` writes to files. See [`WriteMode`].
+    pub fn with_write_mode(mut self, write_mode: WriteMode) -> Self {
+        self.write_mode = write_mode;
+        self
+    }
+
+    /// Confines the handler to the directory `root`.
+    /// All names are then resolved relative to `root`, and names that are absolute,
+    /// that escape `root` using `..` components or that lead outside of `root` through symbolic links
+    /// are rejected with an error of kind [`ErrorKind::PermissionDenied`].
+    /// Method `list_texts` returns names relative to `root`.
+    ///
+    /// Symbolic links are checked before each operation, so the handler can't protect against
+    /// links being changed concurrently by other processes.
+    /// # Examples
+    /// ```
+    /// use std::ffi::OsStr;
+    /// use std::fs::create_dir_all;
+    /// use std::io::ErrorKind;
+    /// use string_io_and_mock::{FileTextHandler, TextIOHandler};
+    ///
+    /// create_dir_all("tests/playground/sandbox").unwrap();
+    /// let mut fth = FileTextHandler::new().with_root("tests/playground/sandbox");
+    ///
+    /// fth.write_text(OsStr::new("notes.txt"), String::from("Stay inside.")).unwrap();
+    ///
+    /// let err = fth.read_text(OsStr::new("../../../Cargo.toml")).unwrap_err();
+    /// assert_eq!(ErrorKind::PermissionDenied, err.kind());
+    /// ```
+    pub fn with_root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Makes the methods that create texts, i.e. `write_text`, `append_text`, `write_bytes`
+    /// and `write_text_if_changed`, and the targets of `rename_text` and `copy_text`,
+    /// create the missing parent directories of the texts first.
+    /// # Examples
+    /// ```
+    /// use std::ffi::OsStr;
+    /// use string_io_and_mock::{FileTextHandler, TextIOHandler};
+    ///
+    /// let mut fth = FileTextHandler::new().with_parent_dirs(true);
+    ///
+    /// fth.write_text(OsStr::new("tests/playground/out/2024/report.txt"), String::from("Done.")).unwrap();
+    /// ```
+    pub fn with_parent_dirs(mut self, parent_dirs: bool) -> Self {
+        self.parent_dirs = parent_dirs;
+        self
+    }
+
+    /// Sets the permissions of the directories created by the handler, e.g. `0o750`,
+    /// which are otherwise `0o777`. The process's umask applies in both cases.
+    #[cfg(unix)]
+    pub fn with_dir_mode(mut self, mode: u32) -> Self {
+        self.dir_mode = Some(mode);
+        self
+    }
+
+    /// Creates the missing parent directories of `path` if the handler is set to do so.
+    fn create_parent(&self, path: &Path) -> IoResult<()> {
+        match path.parent() {
+            Some(parent) if self.parent_dirs && !parent.as_os_str().is_empty() => self.create_dirs(parent),
+            _ => Ok(()),
+        }
+    }
+
+    fn create_dirs(&self, path: &Path) -> IoResult<()> {
+        let mut builder = DirBuilder::new();
+        builder.recursive(true);
+
+        #[cfg(unix)]
+        if let Some(mode) = self.dir_mode {
+            builder.mode(mode);
+        }
+
+        builder.create(path)
     }
+
+    /// Writes `content` to the file at `path` according to the handler's [`WriteMode`].
+    fn write_file(&self, path: &Path, content: &[u8]) -> IoResult<()> {
+        self.create_parent(path)?;
+
+        match self.write_mode {
+            WriteMode::Direct => write(path, content),
+            WriteMode::Atomic => write_atomically(path, content, false),
+            WriteMode::AtomicSynced => write_atomically(path, content, true),
+        }
+    }
+
+    /// Returns the names of the texts at or below `prefix` that may match the glob components `pattern`.
+    fn list_matching(&self, prefix: &OsStr, pattern: &[&str]) -> IoResult<Vec<OsString>> {
+        let path = self.resolve(prefix)?;
+        let names = list_file_names(path.as_os_str(), pattern)?;
+
+        match &self.root {
+            None => Ok(names),
+            Some(root) => Ok(names
+                .iter()
+                .filter_map(|name| Path::new(name).strip_prefix(root).ok())
+                .map(|relative| relative.as_os_str().to_os_string())
+                .collect()),
+        }
+    }
+
+    /// Returns the path of the file holding the text with the given name.
+    fn resolve(&self, name: &OsStr) -> IoResult<PathBuf> {
+        match &self.root {
+            None => Ok(PathBuf::from(name)),
+            Some(root) => path::resolve_in_root(root, name),
+        }
+    }
+}
+
+/// Determines how [`FileTextHandler`] writes texts to files.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use string_io_and_mock::{FileTextHandler, TextIOHandler, WriteMode};
+///
+/// let file_name = OsStr::new("tests/playground/settings.toml");
+/// let mut fth = FileTextHandler::new().with_write_mode(WriteMode::AtomicSynced);
+///
+/// // Readers of the file will only ever see its previous or its new content.
+/// fth.write_text(&file_name, String::from("verbose = true")).unwrap();
+///
+/// assert_eq!("verbose = true", fth.read_text(&file_name).unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WriteMode {
+    /// The target file is truncated and written to in place.
+    /// A crash during the write can leave the file truncated or partly written.
+    #[default]
+    Direct,
+
+    /// The content is written to a temporary file next to the target,
+    /// which is then renamed over the target.
+    /// The temporary file gets the target's permissions from the start,
+    /// and if the target is a symbolic link, the file it leads to is replaced.
+    Atomic,
+
+    /// Like `Atomic`, but the temporary file and the directory holding it are flushed to disk,
+    /// so that the new content also survives a power failure once `write_text` returns.
+    AtomicSynced,
 }
 
 impl TextIOHandler for FileTextHandler {
 
     fn read_text(&self, name: &OsStr) -> IoResult<String> {
-        read_to_string(name)
+        let result = self.resolve(name).and_then(read_to_string);
+
+        annotate(result, TextOperation::Read, name, None)
     }
 
     fn write_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
-        match write(name, content) {
-            Ok(_) => Ok(()),
+        let result = self.resolve(name).and_then(|path| self.write_file(&path, content.as_bytes()));
+
+        annotate(result, TextOperation::Write, name, None)
+    }
+
+    fn append_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        let result = self.resolve(name).and_then(|path| {
+            self.create_parent(&path)?;
+
+            OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(path)?
+                .write_all(content.as_bytes())
+        });
+
+        annotate(result, TextOperation::Append, name, None)
+    }
+
+    fn delete_text(&mut self, name: &OsStr) -> IoResult<()> {
+        let result = self.resolve(name).and_then(remove_file);
+
+        annotate(result, TextOperation::Delete, name, None)
+    }
+
+    fn text_exists(&self, name: &OsStr) -> IoResult<bool> {
+        let result = self.resolve(name).and_then(|path| match metadata(path) {
+            Ok(meta) => Ok(meta.is_file()),
+            Err(io_err) if io_err.kind() == ErrorKind::NotFound => Ok(false),
             Err(io_err) => Err(io_err),
+        });
+
+        annotate(result, TextOperation::Exists, name, None)
+    }
+
+    fn rename_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        let result = self.resolve(from).and_then(|from_path| {
+            let to_path = self.resolve(to)?;
+            self.create_parent(&to_path)?;
+            rename(from_path, to_path)
+        });
+
+        annotate(result, TextOperation::Rename, from, Some(to))
+    }
+
+    fn copy_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        let result = self.resolve(from).and_then(|from_path| {
+            let to_path = self.resolve(to)?;
+            self.create_parent(&to_path)?;
+
+            match is_same_file(&from_path, &to_path) {
+                true => Ok(()),
+                false => copy(from_path, to_path).map(|_| ()),
+            }
+        });
+
+        annotate(result, TextOperation::Copy, from, Some(to))
+    }
+
+    fn list_texts(&self, prefix: &OsStr) -> IoResult<Vec<OsString>> {
+        annotate(self.list_matching(prefix, &["**"]), TextOperation::List, prefix, None)
+    }
+
+    fn glob_texts(&self, pattern: &str) -> IoResult<Vec<OsString>> {
+        let prefix = glob::literal_prefix(pattern);
+        let result = self
+            .list_matching(OsStr::new(&prefix), &glob::wildcard_components(pattern))
+            .map(|names| names
+                .into_iter()
+                .filter(|name| name.to_str().is_some_and(|name| glob_match(pattern, name)))
+                .collect());
+
+        annotate(result, TextOperation::List, OsStr::new(&prefix), None)
+    }
+
+    fn create_dir_all(&mut self, name: &OsStr) -> IoResult<()> {
+        let result = self.resolve(name).and_then(|path| self.create_dirs(&path));
+
+        annotate(result, TextOperation::CreateDir, name, None)
+    }
+
+    /// Compares the content of the file byte by byte, and reports errors as errors of `write_text`.
+    fn write_text_if_changed(&mut self, name: &OsStr, content: String) -> IoResult<bool> {
+        let result = self.resolve(name).and_then(|path| {
+            if fs::read(&path).is_ok_and(|existing| existing == content.as_bytes()) {
+                return Ok(false);
+            }
+
+            self.write_file(&path, content.as_bytes()).map(|_| true)
+        });
+
+        annotate(result, TextOperation::Write, name, None)
+    }
+
+    /// Reports errors as errors of `read_text`.
+    fn peek_text(&self, name: &OsStr) -> IoResult<Option<String>> {
+        let result = self.resolve(name).and_then(fs::read);
+
+        annotate(lossy_if_present(result), TextOperation::Read, name, None)
+    }
+}
+
+impl BytesIOHandler for FileTextHandler {
+
+    fn read_bytes(&self, name: &OsStr) -> IoResult<Vec<u8>> {
+        let result = self.resolve(name).and_then(fs::read);
+
+        annotate(result, TextOperation::ReadBytes, name, None)
+    }
+
+    fn write_bytes(&mut self, name: &OsStr, content: Vec<u8>) -> IoResult<()> {
+        let result = self.resolve(name).and_then(|path| self.write_file(&path, &content));
+
+        annotate(result, TextOperation::WriteBytes, name, None)
+    }
+
+    /// Reports errors as errors of `read_bytes`.
+    fn peek_bytes(&self, name: &OsStr) -> IoResult<Option<Vec<u8>>> {
+        let result = self.resolve(name).and_then(fs::read);
+
+        annotate(if_present(result), TextOperation::ReadBytes, name, None)
+    }
+}
+
+/// Returns the sorted names of the files at or below `prefix`
+/// whose path below it may match the glob components `pattern`; `["**"]` keeps them all.
+fn list_file_names(prefix: &OsStr, pattern: &[&str]) -> IoResult<Vec<OsString>> {
+    let mut names = Vec::new();
+    let prefix_path = Path::new(prefix);
+
+    if prefix.is_empty() {
+        collect_file_names(prefix_path, pattern, &mut names)?;
+    } else {
+        match metadata(prefix_path) {
+            Ok(meta) if meta.is_dir() => collect_file_names(prefix_path, pattern, &mut names)?,
+            Ok(meta) if meta.is_file() => names.push(prefix.to_os_string()),
+            Ok(_) => (),
+            Err(io_err) if io_err.kind() == ErrorKind::NotFound => (),
+            Err(io_err) => return Err(io_err),
+        }
+    }
+
+    names.sort();
+    Ok(names)
+}
+
+/// Converts the result of reading content for methods `peek_text` and `peek_bytes`.
+fn if_present<T>(result: IoResult<T>) -> IoResult<Option<T>> {
+    match result {
+        Ok(content) => Ok(Some(content)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Converts the result of reading a text's bytes for method `peek_text`.
+fn lossy_if_present(result: IoResult<Vec<u8>>) -> IoResult<Option<String>> {
+    if_present(result).map(|content| content.map(|content| String::from_utf8_lossy(&content).into_owned()))
+}
+
+/// Returns whether both paths lead to the same existing file,
+/// e.g. through `..` components, symbolic links or, on Unix, hard links.
+#[cfg(unix)]
+fn is_same_file(first: &Path, second: &Path) -> bool {
+    match (metadata(first), metadata(second)) {
+        (Ok(first), Ok(second)) => (first.dev(), first.ino()) == (second.dev(), second.ino()),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_same_file(first: &Path, second: &Path) -> bool {
+    match (first.canonicalize(), second.canonicalize()) {
+        (Ok(first), Ok(second)) => first == second,
+        _ => false,
+    }
+}
+
+/// Writes `content` to a temporary file next to `path` and renames it over `path`.
+/// If `path` is a symbolic link, the file it leads to is replaced, rather than the link.
+/// If `sync` is true, the temporary file and its directory are flushed to disk.
+fn write_atomically(path: &Path, content: &[u8], sync: bool) -> IoResult<()> {
+    static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let path = &follow_symlinks(path)?;
+    let file_name = path.file_name().ok_or_else(|| IoError::from(ErrorKind::InvalidInput))?;
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.{}.tmp", process::id(), TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)));
+    let temp_path = dir.join(temp_name);
+
+    // Replacing the target shouldn't change who may access it,
+    // not even while the temporary file holds the new content.
+    let permissions = metadata(path).ok().map(|meta| meta.permissions());
+
+    let result = (|| {
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        if let Some(permissions) = &permissions {
+            options.mode(permissions.mode());
+        }
+
+        let mut temp_file = options.open(&temp_path)?;
+
+        // The mode given at creation is restricted by the umask.
+        if let Some(permissions) = permissions {
+            temp_file.set_permissions(permissions)?;
+        }
+
+        temp_file.write_all(content)?;
+
+        if sync {
+            temp_file.sync_all()?;
+        }
+
+        drop(temp_file);
+        rename(&temp_path, path)?;
+
+        if sync {
+            sync_dir(dir)?;
+        }
+
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = remove_file(&temp_path);
+    }
+
+    result
+}
+
+/// Returns the path a chain of symbolic links leads to, which may not exist yet.
+/// Paths that aren't symbolic links are returned as they are.
+fn follow_symlinks(path: &Path) -> IoResult<PathBuf> {
+    // The same limit as Linux, to stop at cycles.
+    const MAX_LINKS: usize = 40;
+
+    let mut path = path.to_path_buf();
+
+    for _ in 0..MAX_LINKS {
+        match fs::symlink_metadata(&path) {
+            Ok(meta) if meta.file_type().is_symlink() => {
+                let target = fs::read_link(&path)?;
+                path = match path.parent() {
+                    Some(parent) => parent.join(target),
+                    None => target,
+                };
+            },
+            _ => return Ok(path),
         }
     }
+
+    Err(IoError::new(ErrorKind::InvalidInput, "too many levels of symbolic links"))
+}
+
+/// Flushes a directory's entries to disk, so that a rename in it is durable.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> IoResult<()> {
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> IoResult<()> {
+    Ok(())
+}
+
+/// Recursively adds the names of the files below `dir` to `names`.
+/// Only entries matching the glob components `pattern` are considered, component by component,
+/// so directories the pattern can't reach aren't read at all.
+/// Symbolic links to directories aren't followed, so as to avoid cycles.
+fn collect_file_names(dir: &Path, pattern: &[&str], names: &mut Vec<OsString>) -> IoResult<()> {
+    let Some((first, rest)) = pattern.split_first() else {
+        return Ok(());
+    };
+    let dir_to_read = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+
+    for entry in read_dir(dir_to_read)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let remaining = match *first {
+            "**" => pattern,
+            _ => match file_name.to_str() {
+                Some(part) if glob::match_component(first, part) => rest,
+                _ => continue,
+            },
+        };
+        let path = dir.join(file_name);
+
+        if entry.file_type()?.is_dir() {
+            collect_file_names(&path, remaining, names)?;
+        } else if path.is_file() {
+            names.push(path.into_os_string());
+        }
+    }
+
+    Ok(())
 }
 
+/// The texts stored by a [`MockTextHandler`], shared with its snapshots until either changes.
+type TextMap = HashMap<OsString, Arc<Vec<u8>>>;
+
 /// MockTextHandler allows FileTextHandler objects to be replaced by a mock in unit tests.
 /// MockTextHandler stores strings written to it in a private [`HashMap`].
 /// # Examples
@@ -103,98 +811,1131 @@ impl TextIOHandler for FileTextHandler {
 /// }
 /// ```
 pub struct MockTextHandler {
-    texts: HashMap<OsString, String>,
+    texts: Arc<TextMap>,
+    failures: RefCell<Vec<FailureRule>>,
+    calls: RefCell<Vec<TextCall>>,
+    expectations: Vec<Expectation>,
+    strict: bool,
+    sandboxed: bool,
+    normalizing: bool,
+    emulating_fs: bool,
+    creating_parents: bool,
+    dirs: Arc<HashSet<OsString>>,
+    panic_export_dir: Option<PathBuf>,
 }
 
 impl MockTextHandler {
     pub fn new() -> Self {
         MockTextHandler {
-            texts: HashMap::new(),
+            texts: Arc::new(HashMap::new()),
+            failures: RefCell::new(Vec::new()),
+            calls: RefCell::new(Vec::new()),
+            expectations: Vec::new(),
+            strict: false,
+            sandboxed: false,
+            normalizing: true,
+            emulating_fs: false,
+            creating_parents: false,
+            dirs: Arc::new(HashSet::new()),
+            panic_export_dir: None,
         }
     }
-}
 
-impl Default for MockTextHandler {
-    fn default() -> Self {
-        Self::new()
+    /// Stores a text without recording a call or checking failure rules and expectations,
+    /// so as to set up a mock for a test.
+    pub fn with_text<N: AsRef<OsStr>, C: Into<String>>(self, name: N, content: C) -> Self {
+        self.with_bytes(name, content.into().into_bytes())
     }
-}
-
-impl TextIOHandler for MockTextHandler {
 
-    fn read_text(&self, name: &OsStr) -> IoResult<String> {
-        match self.texts.get(&name.to_os_string()) {
-            None => Err(IoError::from(ErrorKind::NotFound)),
-            Some(content) => Ok(content.clone()),
-        }
+    /// Stores byte content without recording a call or checking failure rules and expectations,
+    /// so as to set up a mock for a test.
+    /// Content that isn't valid UTF-8 makes `read_text` fail with [`ErrorKind::InvalidData`]
+    /// exactly like [`std::fs::read_to_string`] does.
+    /// # Examples
+    /// ```
+    /// use std::ffi::OsStr;
+    /// use std::io::ErrorKind;
+    /// use string_io_and_mock::{BytesIOHandler, MockTextHandler, TextIOHandler};
+    ///
+    /// let mock = MockTextHandler::new().with_bytes("corrupt.txt", b"caf\xe9".to_vec());
+    ///
+    /// let err = mock.read_text(OsStr::new("corrupt.txt")).unwrap_err();
+    /// assert_eq!(ErrorKind::InvalidData, err.kind());
+    ///
+    /// assert_eq!("caf\u{FFFD}", mock.read_text_lossy(OsStr::new("corrupt.txt")).unwrap());
+    /// ```
+    pub fn with_bytes<N: AsRef<OsStr>>(mut self, name: N, content: Vec<u8>) -> Self {
+        let name = name.as_ref();
+        let key = self.key(name).unwrap_or_else(|_| name.to_os_string());
+        self.texts_mut().insert(key, Arc::new(content));
+        self
     }
 
-    fn write_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
-        self.texts.insert(name.to_os_string(), content);
-        Ok(())
+    /// Makes calls matching the given rule fail. See [`FailureRule`].
+    /// Failing calls leave the stored texts unchanged.
+    /// If several rules match a call, the error of the first one injected is returned.
+    pub fn inject_failure(&mut self, rule: FailureRule) {
+        self.failures.get_mut().push(rule);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Adds an expectation for calls of the given operation on the given name
+    /// and returns it for further configuration. See [`Expectation`].
+    pub fn expect<N: AsRef<OsStr>>(&mut self, operation: TextOperation, name: N) -> &mut Expectation {
+        self.expectations.push(Expectation::new(operation, name.as_ref()));
+        self.expectations.last_mut().unwrap()
+    }
 
-    #[test]
-    fn mock_read_write() {
-        let txt = String::from("\
-As I came down by Fiddichside on a May morning
-I spied Willy MacIntosh an hour before the dawning.
-Turn again, turn again, turn again I bid thee,
-If ye'll burn Auchindoon, Huntley he will heed thee.
+    /// Adds an expectation for calls to `read_text` on the given name.
+    pub fn expect_read<N: AsRef<OsStr>>(&mut self, name: N) -> &mut Expectation {
+        self.expect(TextOperation::Read, name)
+    }
 
-- Heed me or hang me, that will never fear me.
-I'll burn Auchindoon ere the life will leave me.
+    /// Adds an expectation for calls to `write_text` on the given name.
+    pub fn expect_write<N: AsRef<OsStr>>(&mut self, name: N) -> &mut Expectation {
+        self.expect(TextOperation::Write, name)
+    }
 
-As I came down by Fiddichside on a May morning
-Auchindoon was in a blaze an hour before the dawning.
-Crawing, crawing, for all your crews are crawing,
-you taint your crops and burnt your wings
-an hour before the dawning.
+    /// Adds an expectation for calls to `append_text` on the given name.
+    pub fn expect_append<N: AsRef<OsStr>>(&mut self, name: N) -> &mut Expectation {
+        self.expect(TextOperation::Append, name)
+    }
 
-As I came down by Fiddichside on a May morning
-I spied Willy MacIntosh an hour before the dawning.
-Hanging, hanging, ay the boy was hanging,
-but the smoke of Auchindoon
-through the air was rising.
-");
+    /// In strict mode, a call that isn't accepted by any expectation makes the mock panic.
+    /// By default, the mock isn't strict, and such calls simply access the stored texts.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
 
-        let key = OsStr::new("Auchindoon");
-        let mut mock = MockTextHandler::default();
-        mock.write_text(&key, txt.clone()).unwrap();
-        let read_back = mock.read_text(&key).unwrap();
+    /// In sandboxed mode, the mock rejects the same names as a [`FileTextHandler`] confined
+    /// to a root directory using `with_root` : absolute names and names escaping the root
+    /// using `..` components yield an error of kind [`ErrorKind::PermissionDenied`].
+    /// Other names are resolved relative to the root, so `a/../b.txt` and `b.txt` are the same text.
+    pub fn set_sandboxed(&mut self, sandboxed: bool) {
+        self.sandboxed = sandboxed;
+    }
 
-        assert_eq!(txt, read_back);
+    /// With path normalization, which is on by default, names that a file system on Linux
+    /// would consider the same file refer to the same text : `a/./b.txt`, `a//b.txt`, `./a/b.txt`
+    /// and `a/c/../b.txt` are all stored as `a/b.txt`, which is also the name `list_texts` returns.
+    /// As the mock has no directories or symbolic links, `..` components simply cancel
+    /// the preceding component.
+    ///
+    /// Failure rules, expectations and the methods querying recorded calls, like `writes_to`,
+    /// compare names the same way, although the calls are recorded with names as they were passed.
+    pub fn set_path_normalization(&mut self, normalizing: bool) {
+        self.normalizing = normalizing;
     }
 
-    #[test]
-    fn mock_overwrite() {
-        let txt1 = String::from("Well, about the well :");
-        let txt2 = String::from("One can move the city, but not the well.");
+    /// In file system emulation mode, the mock keeps track of directories, which are created
+    /// using method `create_dir_all`, and fails where a file system would :
+    /// - writing a text whose parent directory doesn't exist yields [`ErrorKind::NotFound`];
+    /// - accessing a directory as a text yields [`ErrorKind::IsADirectory`];
+    /// - accessing a text below another text yields [`ErrorKind::NotADirectory`].
+    ///
+    /// When the mode is switched on, the parent directories of the texts already stored are created.
+    /// # Examples
+    /// ```
+    /// use std::ffi::OsStr;
+    /// use std::io::ErrorKind;
+    /// use string_io_and_mock::{MockTextHandler, TextIOHandler};
+    ///
+    /// let mut mock = MockTextHandler::new();
+    /// mock.set_fs_emulation(true);
+    ///
+    /// let err = mock.write_text(OsStr::new("out/report.txt"), String::new()).unwrap_err();
+    /// assert_eq!(ErrorKind::NotFound, err.kind());
+    ///
+    /// mock.create_dir_all(OsStr::new("out")).unwrap();
+    /// mock.write_text(OsStr::new("out/report.txt"), String::new()).unwrap();
+    /// ```
+    pub fn set_fs_emulation(&mut self, emulating_fs: bool) {
+        self.emulating_fs = emulating_fs;
 
-        let key = OsStr::new("The Well");
-        let mut mock = MockTextHandler::new();
-        mock.write_text(&key, txt1.clone()).unwrap();
-        mock.write_text(&key, txt2.clone()).unwrap();
-        let read_back = mock.read_text(&key).unwrap();
+        if emulating_fs {
+            let parents: Vec<OsString> = self.texts
+                .keys()
+                .filter_map(|name| Path::new(name).parent())
+                .map(|parent| parent.as_os_str().to_os_string())
+                .collect();
 
-        assert_eq!(txt2, read_back);
+            for parent in parents {
+                let _ = self.create_dir_stored(&parent);
+            }
+        }
     }
 
-    #[test]
-    fn mock_read_missing() {
-        let mock = MockTextHandler::new();
-        let result = mock.read_text(&OsStr::new("Whatever"));
+    /// Makes the mock create the missing parent directories of the texts it creates,
+    /// like a [`FileTextHandler`] set up using `with_parent_dirs` does.
+    /// This only matters in file system emulation mode.
+    /// # Examples
+    /// ```
+    /// use std::ffi::OsStr;
+    /// use string_io_and_mock::{MockTextHandler, TextIOHandler};
+    ///
+    /// let mut mock = MockTextHandler::new();
+    /// mock.set_fs_emulation(true);
+    /// mock.set_parent_dirs(true);
+    ///
+    /// mock.write_text(OsStr::new("out/2024/report.txt"), String::new()).unwrap();
+    /// assert_eq!(vec!["out/2024/report.txt"], mock.list_texts(OsStr::new("out")).unwrap());
+    /// ```
+    pub fn set_parent_dirs(&mut self, creating_parents: bool) {
+        self.creating_parents = creating_parents;
+    }
 
-        match result {
-            Ok(_) => panic!("Method read_text should return an Err if no text with the passed name is found."),
-            Err(err) => {
+    /// Makes the mock export its texts to the directory `dir` using method `export_to_dir`
+    /// when it's dropped while the thread is panicking, e.g. because a test assertion failed,
+    /// so that the texts written by the code under test can be inspected.
+    /// The directory the texts were exported to, or the reason the export failed, is printed to stderr.
+    pub fn export_on_panic<P: Into<PathBuf>>(&mut self, dir: P) {
+        self.panic_export_dir = Some(dir.into());
+    }
+
+    /// Checks that all expectations have been met.
+    /// This happens automatically when the mock is dropped, unless the thread is already panicking.
+    /// # Panics
+    /// Panics listing the unmet expectations, if any.
+    pub fn verify(&self) {
+        let failures: Vec<String> = self.expectations
+            .iter()
+            .filter(|expectation| !expectation.is_met())
+            .map(Expectation::describe_failure)
+            .collect();
+
+        if !failures.is_empty() {
+            panic!("MockTextHandler expectations not met :\n{}", failures.join("\n"));
+        }
+    }
+
+    /// Removes all injected failure rules.
+    pub fn clear_failures(&mut self) {
+        self.failures.get_mut().clear();
+    }
+
+    /// Returns all calls made to this mock's [`TextIOHandler`] methods so far, in order.
+    pub fn calls(&self) -> Vec<TextCall> {
+        self.calls.borrow().clone()
+    }
+
+    /// Returns the number of calls made to this mock's [`TextIOHandler`] methods so far.
+    pub fn call_count(&self) -> usize {
+        self.calls.borrow().len()
+    }
+
+    /// Returns the calls to `read_text` for the given name, in order.
+    pub fn reads_of<N: AsRef<OsStr>>(&self, name: N) -> Vec<TextCall> {
+        self.calls_matching(TextOperation::Read, name.as_ref())
+    }
+
+    /// Returns the calls to `write_text` for the given name, in order.
+    pub fn writes_to<N: AsRef<OsStr>>(&self, name: N) -> Vec<TextCall> {
+        self.calls_matching(TextOperation::Write, name.as_ref())
+    }
+
+    /// Returns the calls to `append_text` for the given name, in order.
+    pub fn appends_to<N: AsRef<OsStr>>(&self, name: N) -> Vec<TextCall> {
+        self.calls_matching(TextOperation::Append, name.as_ref())
+    }
+
+    /// Forgets all calls recorded so far.
+    pub fn clear_calls(&mut self) {
+        self.calls.get_mut().clear();
+    }
+
+    fn calls_matching(&self, operation: TextOperation, name: &OsStr) -> Vec<TextCall> {
+        self.calls
+            .borrow()
+            .iter()
+            .filter(|call| call.operation == operation && self.name_key(&call.name) == self.name_key(name))
+            .cloned()
+            .collect()
+    }
+
+    /// Records a call and wraps its error, if any, in a [`TextIOError`].
+    fn finish<T>(&self, mut call: TextCall, result: IoResult<T>) -> IoResult<T> {
+        let result = annotate(result, call.operation, &call.name, call.target.as_deref());
+        call.error = result.as_ref().err().map(IoError::kind);
+        self.calls.borrow_mut().push(call);
+
+        result
+    }
+
+    /// Registers a call with the expectations and failure rules.
+    /// Returns the result the call should produce instead of accessing the stored texts, if any.
+    fn intercept(&self, call: &TextCall) -> Option<IoResult<String>> {
+        if let Some(result) = self.check_expectations(call) {
+            return Some(result);
+        }
+
+        let mut names = vec![call.name.as_os_str()];
+        names.extend(call.target.as_deref());
+
+        let mut injected = None;
+
+        for rule in self.failures.borrow_mut().iter_mut() {
+            let error = rule.check(call.operation, &names, |name| self.name_key(name));
+
+            if injected.is_none() {
+                injected = error;
+            }
+        }
+
+        injected.map(Err)
+    }
+
+    /// Registers a call with the first matching expectation.
+    /// Returns the result set on that expectation, if any.
+    /// # Panics
+    /// Panics if the mock is strict and no expectation accepts the call.
+    fn check_expectations(&self, call: &TextCall) -> Option<IoResult<String>> {
+        let mut matching = self.expectations.iter().filter(|expectation| expectation.matches(call, |name| self.name_key(name)));
+        let accepting = matching.clone().find(|expectation| !expectation.is_saturated());
+
+        match accepting.or_else(|| matching.next()) {
+            Some(expectation) => {
+                expectation.register_call();
+
+                if self.strict && expectation.is_exceeded() {
+                    panic!("{}", expectation.describe_failure());
+                }
+
+                expectation.result()
+            },
+            None if self.strict => panic!("Unexpected call to {} of {:?}.", call.operation, call.name),
+            None => None,
+        }
+    }
+}
+
+impl Default for MockTextHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for MockTextHandler {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            self.verify();
+        } else if let Some(dir) = &self.panic_export_dir {
+            match self.export_to_dir(dir) {
+                Ok(()) => eprintln!("The texts of the mock were exported to {}.", dir.display()),
+                Err(err) => eprintln!("The texts of the mock couldn't be exported to {} : {}", dir.display(), err),
+            }
+        }
+    }
+}
+
+impl TextIOHandler for MockTextHandler {
+
+    fn read_text(&self, name: &OsStr) -> IoResult<String> {
+        let mut call = TextCall::new(TextOperation::Read, name, None, None);
+        let result = match self.intercept(&call) {
+            Some(result) => result,
+            None => self.read_stored(name),
+        };
+
+        call.content = result.as_ref().ok().cloned();
+        self.finish(call, result)
+    }
+
+    fn write_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        let call = TextCall::new(TextOperation::Write, name, None, Some(content.clone()));
+        let result = match self.intercept(&call) {
+            Some(result) => result.map(|_| ()),
+            None => self.write_stored(name, content),
+        };
+
+        self.finish(call, result)
+    }
+
+    fn append_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        let call = TextCall::new(TextOperation::Append, name, None, Some(content.clone()));
+        let result = match self.intercept(&call) {
+            Some(result) => result.map(|_| ()),
+            None => self.append_stored(name, content),
+        };
+
+        self.finish(call, result)
+    }
+
+    fn delete_text(&mut self, name: &OsStr) -> IoResult<()> {
+        let call = TextCall::new(TextOperation::Delete, name, None, None);
+        let result = match self.intercept(&call) {
+            Some(result) => result.map(|_| ()),
+            None => self.delete_stored(name),
+        };
+
+        self.finish(call, result)
+    }
+
+    fn text_exists(&self, name: &OsStr) -> IoResult<bool> {
+        let call = TextCall::new(TextOperation::Exists, name, None, None);
+        let result = match self.intercept(&call) {
+            Some(result) => result.map(|_| true),
+            None => self.exists_stored(name),
+        };
+
+        self.finish(call, result)
+    }
+
+    fn rename_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        let call = TextCall::new(TextOperation::Rename, from, Some(to), None);
+        let result = match self.intercept(&call) {
+            Some(result) => result.map(|_| ()),
+            None => self.rename_stored(from, to),
+        };
+
+        self.finish(call, result)
+    }
+
+    fn copy_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        let call = TextCall::new(TextOperation::Copy, from, Some(to), None);
+        let result = match self.intercept(&call) {
+            Some(result) => result.map(|_| ()),
+            None => self.copy_stored(from, to),
+        };
+
+        self.finish(call, result)
+    }
+
+    fn list_texts(&self, prefix: &OsStr) -> IoResult<Vec<OsString>> {
+        let call = TextCall::new(TextOperation::List, prefix, None, None);
+        let result = match self.intercept(&call) {
+            Some(result) => result.map(|_| Vec::new()),
+            None => self.list_stored(prefix),
+        };
+
+        self.finish(call, result)
+    }
+
+    fn create_dir_all(&mut self, name: &OsStr) -> IoResult<()> {
+        let call = TextCall::new(TextOperation::CreateDir, name, None, None);
+        let result = match self.intercept(&call) {
+            Some(result) => result.map(|_| ()),
+            None => self.create_dir_stored(name),
+        };
+
+        self.finish(call, result)
+    }
+
+    /// Compares the stored content without recording a call, so an unchanged text records no call at all.
+    /// A changed text is written using `write_text`, which is recorded as usual.
+    fn write_text_if_changed(&mut self, name: &OsStr, content: String) -> IoResult<bool> {
+        if self.read_bytes_stored(name).is_ok_and(|existing| existing == content.as_bytes()) {
+            return Ok(false);
+        }
+
+        self.write_text(name, content).map(|_| true)
+    }
+
+    fn peek_text(&self, name: &OsStr) -> IoResult<Option<String>> {
+        let result = lossy_if_present(self.read_bytes_stored(name));
+
+        annotate(result, TextOperation::Read, name, None)
+    }
+}
+
+impl BytesIOHandler for MockTextHandler {
+
+    fn read_bytes(&self, name: &OsStr) -> IoResult<Vec<u8>> {
+        let call = TextCall::new(TextOperation::ReadBytes, name, None, None);
+        let result = match self.intercept(&call) {
+            Some(result) => result.map(String::into_bytes),
+            None => self.read_bytes_stored(name),
+        };
+
+        self.finish(call, result)
+    }
+
+    fn write_bytes(&mut self, name: &OsStr, content: Vec<u8>) -> IoResult<()> {
+        let call = TextCall::new(TextOperation::WriteBytes, name, None, None);
+        let result = match self.intercept(&call) {
+            Some(result) => result.map(|_| ()),
+            None => self.write_bytes_stored(name, content),
+        };
+
+        self.finish(call, result)
+    }
+
+    fn peek_bytes(&self, name: &OsStr) -> IoResult<Option<Vec<u8>>> {
+        annotate(if_present(self.read_bytes_stored(name)), TextOperation::ReadBytes, name, None)
+    }
+}
+
+/// The operations on the stored texts, without failure injection or call recording.
+impl MockTextHandler {
+
+    /// Returns the key under which the text with the given name is stored.
+    fn key(&self, name: &OsStr) -> IoResult<OsString> {
+        if self.sandboxed {
+            Ok(path::confine(name)?.into_os_string())
+        } else if self.normalizing {
+            Ok(path::normalize(name).into_os_string())
+        } else {
+            Ok(name.to_os_string())
+        }
+    }
+
+    /// Returns the key under which the text with the given name is stored, or the name itself
+    /// if it can't be stored, for comparing names the way the stored texts do.
+    fn name_key(&self, name: &OsStr) -> OsString {
+        self.key(name).unwrap_or_else(|_| name.to_os_string())
+    }
+
+    /// Returns the key under which the text with the given name is stored,
+    /// after checking, in file system emulation mode, that a file system would allow
+    /// accessing the text, or creating it if `creating` is true.
+    fn checked_key(&self, name: &OsStr, creating: bool) -> IoResult<OsString> {
+        let key = self.key(name)?;
+
+        if self.emulating_fs {
+            let path = Path::new(&key);
+
+            if path.ancestors().skip(1).any(|ancestor| self.texts.contains_key(ancestor.as_os_str())) {
+                return Err(IoError::from(ErrorKind::NotADirectory));
+            }
+
+            if self.dirs.contains(&key) {
+                return Err(IoError::from(ErrorKind::IsADirectory));
+            }
+
+            if creating && !path.parent().is_none_or(|parent| self.dir_exists(parent)) {
+                return Err(IoError::from(ErrorKind::NotFound));
+            }
+        }
+
+        Ok(key)
+    }
+
+    /// Gives mutable access to the stored texts, copying them first if they're shared with a snapshot.
+    fn texts_mut(&mut self) -> &mut TextMap {
+        Arc::make_mut(&mut self.texts)
+    }
+
+    /// Returns the key under which a text with the given name can be created, like `checked_key`,
+    /// after creating its missing parent directories if the mock is set to do so.
+    fn creatable_key(&mut self, name: &OsStr) -> IoResult<OsString> {
+        if !(self.emulating_fs && self.creating_parents) {
+            return self.checked_key(name, true);
+        }
+
+        let key = self.checked_key(name, false)?;
+
+        if let Some(parent) = Path::new(&key).parent() {
+            self.create_dir_stored(parent.as_os_str())?;
+        }
+
+        Ok(key)
+    }
+
+    /// The current directory and the root directory always exist.
+    fn dir_exists(&self, path: &Path) -> bool {
+        path.as_os_str().is_empty() || path.parent().is_none() || self.dirs.contains(path.as_os_str())
+    }
+
+    fn read_stored(&self, name: &OsStr) -> IoResult<String> {
+        String::from_utf8(self.read_bytes_stored(name)?)
+            .map_err(|_| IoError::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8"))
+    }
+
+    fn read_bytes_stored(&self, name: &OsStr) -> IoResult<Vec<u8>> {
+        match self.texts.get(&self.checked_key(name, false)?) {
+            None => Err(IoError::from(ErrorKind::NotFound)),
+            Some(content) => Ok(content.to_vec()),
+        }
+    }
+
+    fn write_stored(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        self.write_bytes_stored(name, content.into_bytes())
+    }
+
+    fn write_bytes_stored(&mut self, name: &OsStr, content: Vec<u8>) -> IoResult<()> {
+        let key = self.creatable_key(name)?;
+        self.texts_mut().insert(key, Arc::new(content));
+        Ok(())
+    }
+
+    fn append_stored(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        let key = self.creatable_key(name)?;
+        Arc::make_mut(self.texts_mut().entry(key).or_default()).extend_from_slice(content.as_bytes());
+        Ok(())
+    }
+
+    fn delete_stored(&mut self, name: &OsStr) -> IoResult<()> {
+        let key = self.checked_key(name, false)?;
+
+        match self.texts_mut().remove(&key) {
+            None => Err(IoError::from(ErrorKind::NotFound)),
+            Some(_) => Ok(()),
+        }
+    }
+
+    fn exists_stored(&self, name: &OsStr) -> IoResult<bool> {
+        match self.checked_key(name, false) {
+            Ok(key) => Ok(self.texts.contains_key(&key)),
+            Err(err) if err.kind() == ErrorKind::IsADirectory => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn rename_stored(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        let (from_key, to_key) = (self.checked_key(from, false)?, self.creatable_key(to)?);
+
+        match self.texts_mut().remove(&from_key) {
+            None => Err(IoError::from(ErrorKind::NotFound)),
+            Some(content) => {
+                self.texts_mut().insert(to_key, content);
+                Ok(())
+            },
+        }
+    }
+
+    fn copy_stored(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        let content = self.read_bytes_stored(from)?;
+        let to_key = self.creatable_key(to)?;
+        self.texts_mut().insert(to_key, Arc::new(content));
+        Ok(())
+    }
+
+    fn list_stored(&self, prefix: &OsStr) -> IoResult<Vec<OsString>> {
+        let prefix_key = self.key(prefix)?;
+        let mut names: Vec<OsString> = self.texts
+            .keys()
+            .filter(|name| Path::new(name).starts_with(&prefix_key))
+            .cloned()
+            .collect();
+
+        names.sort();
+        Ok(names)
+    }
+
+    fn create_dir_stored(&mut self, name: &OsStr) -> IoResult<()> {
+        let key = self.key(name)?;
+        let path = Path::new(&key);
+
+        if self.emulating_fs {
+            if self.texts.contains_key(&key) {
+                return Err(IoError::from(ErrorKind::AlreadyExists));
+            }
+
+            if path.ancestors().skip(1).any(|ancestor| self.texts.contains_key(ancestor.as_os_str())) {
+                return Err(IoError::from(ErrorKind::NotADirectory));
+            }
+        }
+
+        let missing: Vec<OsString> = path
+            .ancestors()
+            .filter(|ancestor| !self.dir_exists(ancestor))
+            .map(|ancestor| ancestor.as_os_str().to_os_string())
+            .collect();
+
+        if !missing.is_empty() {
+            Arc::make_mut(&mut self.dirs).extend(missing);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::needless_borrow)]
+    fn mock_read_write() {
+        let txt = String::from("\
+As I came down by Fiddichside on a May morning
+I spied Willy MacIntosh an hour before the dawning.
+Turn again, turn again, turn again I bid thee,
+If ye'll burn Auchindoon, Huntley he will heed thee.
+
+- Heed me or hang me, that will never fear me.
+I'll burn Auchindoon ere the life will leave me.
+
+As I came down by Fiddichside on a May morning
+Auchindoon was in a blaze an hour before the dawning.
+Crawing, crawing, for all your crews are crawing,
+you taint your crops and burnt your wings
+an hour before the dawning.
+
+As I came down by Fiddichside on a May morning
+I spied Willy MacIntosh an hour before the dawning.
+Hanging, hanging, ay the boy was hanging,
+but the smoke of Auchindoon
+through the air was rising.
+");
+
+        let key = OsStr::new("Auchindoon");
+        let mut mock = MockTextHandler::default();
+        mock.write_text(&key, txt.clone()).unwrap();
+        let read_back = mock.read_text(&key).unwrap();
+
+        assert_eq!(txt, read_back);
+    }
+
+    #[test]
+    #[allow(clippy::needless_borrow)]
+    fn mock_overwrite() {
+        let txt1 = String::from("Well, about the well :");
+        let txt2 = String::from("One can move the city, but not the well.");
+
+        let key = OsStr::new("The Well");
+        let mut mock = MockTextHandler::new();
+        mock.write_text(&key, txt1.clone()).unwrap();
+        mock.write_text(&key, txt2.clone()).unwrap();
+        let read_back = mock.read_text(&key).unwrap();
+
+        assert_eq!(txt2, read_back);
+    }
+
+    #[test]
+    #[allow(clippy::needless_borrow)]
+    fn mock_read_missing() {
+        let mock = MockTextHandler::new();
+        let result = mock.read_text(&OsStr::new("Whatever"));
+
+        match result {
+            Ok(_) => panic!("Method read_text should return an Err if no text with the passed name is found."),
+            Err(err) => {
                 assert_eq!(ErrorKind::NotFound, err.kind());
             },
         }
     }
+
+    #[test]
+    fn mock_append() {
+        let key = OsStr::new("Journal");
+        let mut mock = MockTextHandler::new();
+
+        mock.append_text(key, String::from("Monday: rain.\n")).unwrap();
+        mock.append_text(key, String::from("Tuesday: more rain.\n")).unwrap();
+
+        assert_eq!("Monday: rain.\nTuesday: more rain.\n", mock.read_text(key).unwrap());
+    }
+
+    #[test]
+    fn mock_delete() {
+        let key = OsStr::new("Ephemeral");
+        let mut mock = MockTextHandler::new();
+        mock.write_text(key, String::from("Here today, gone tomorrow.")).unwrap();
+        assert!(mock.text_exists(key).unwrap());
+
+        mock.delete_text(key).unwrap();
+        assert!(!mock.text_exists(key).unwrap());
+
+        let err = mock.delete_text(key).unwrap_err();
+        assert_eq!(ErrorKind::NotFound, err.kind());
+    }
+
+    #[test]
+    fn mock_rename() {
+        let txt = String::from("A rose by any other name would smell as sweet.");
+        let from = OsStr::new("Montague");
+        let to = OsStr::new("Capulet");
+        let mut mock = MockTextHandler::new();
+        mock.write_text(from, txt.clone()).unwrap();
+
+        mock.rename_text(from, to).unwrap();
+
+        assert!(!mock.text_exists(from).unwrap());
+        assert_eq!(txt, mock.read_text(to).unwrap());
+
+        let err = mock.rename_text(from, to).unwrap_err();
+        assert_eq!(ErrorKind::NotFound, err.kind());
+    }
+
+    #[test]
+    fn mock_copy() {
+        let txt = String::from("Imitation is the sincerest form of flattery.");
+        let from = OsStr::new("Original");
+        let to = OsStr::new("Copy");
+        let mut mock = MockTextHandler::new();
+        mock.write_text(from, txt.clone()).unwrap();
+
+        mock.copy_text(from, to).unwrap();
+
+        assert_eq!(txt, mock.read_text(from).unwrap());
+        assert_eq!(txt, mock.read_text(to).unwrap());
+
+        mock.copy_text(from, from).unwrap();
+        assert_eq!(txt, mock.read_text(from).unwrap());
+
+        let err = mock.copy_text(OsStr::new("Missing"), to).unwrap_err();
+        assert_eq!(ErrorKind::NotFound, err.kind());
+    }
+
+    fn mock_with_tree() -> MockTextHandler {
+        let mut mock = MockTextHandler::new();
+
+        for name in ["docs/intro.md", "docs/api/calls.md", "docs/api/notes.txt", "docs_old/intro.md", "README.md"] {
+            mock.write_text(OsStr::new(name), String::from(name)).unwrap();
+        }
+
+        mock
+    }
+
+    #[test]
+    fn mock_list() {
+        let mock = mock_with_tree();
+
+        assert_eq!(
+            vec!["docs/api/calls.md", "docs/api/notes.txt", "docs/intro.md"],
+            mock.list_texts(OsStr::new("docs")).unwrap());
+
+        assert_eq!(5, mock.list_texts(OsStr::new("")).unwrap().len());
+        assert!(mock.list_texts(OsStr::new("nowhere")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn mock_glob() {
+        let mock = mock_with_tree();
+
+        assert_eq!(vec!["docs/intro.md"], mock.glob_texts("docs/*.md").unwrap());
+        assert_eq!(
+            vec!["README.md", "docs/api/calls.md", "docs/intro.md", "docs_old/intro.md"],
+            mock.glob_texts("**/*.md").unwrap());
+
+        let mock = MockTextHandler::new().with_text("/etc/app/a.toml", "").with_text("etc/app/b.toml", "");
+        assert_eq!(vec!["/etc/app/a.toml"], mock.glob_texts("/etc/app/*.toml").unwrap());
+        assert_eq!(vec!["etc/app/b.toml"], mock.glob_texts("**/*.toml").unwrap());
+    }
+
+    #[test]
+    fn mock_failure_by_name() {
+        let mut mock = mock_with_tree();
+        mock.inject_failure(FailureRule::new(ErrorKind::PermissionDenied).for_name("docs/intro.md"));
+
+        let err = mock.read_text(OsStr::new("docs/intro.md")).unwrap_err();
+        assert_eq!(ErrorKind::PermissionDenied, err.kind());
+
+        let err = mock.write_text(OsStr::new("docs/intro.md"), String::new()).unwrap_err();
+        assert_eq!(ErrorKind::PermissionDenied, err.kind());
+
+        let err = mock.rename_text(OsStr::new("README.md"), OsStr::new("docs/intro.md")).unwrap_err();
+        assert_eq!(ErrorKind::PermissionDenied, err.kind());
+        assert!(mock.text_exists(OsStr::new("README.md")).unwrap());
+
+        mock.read_text(OsStr::new("README.md")).unwrap();
+
+        mock.clear_failures();
+        mock.read_text(OsStr::new("docs/intro.md")).unwrap();
+    }
+
+    #[test]
+    fn mock_failure_per_operation() {
+        let mut mock = mock_with_tree();
+        mock.inject_failure(FailureRule::new(ErrorKind::Interrupted).on(TextOperation::Read));
+        mock.inject_failure(
+            FailureRule::new(ErrorKind::StorageFull)
+                .on(TextOperation::Write)
+                .on(TextOperation::Append));
+
+        let name = OsStr::new("README.md");
+        assert_eq!(ErrorKind::Interrupted, mock.read_text(name).unwrap_err().kind());
+        assert_eq!(ErrorKind::StorageFull, mock.write_text(name, String::new()).unwrap_err().kind());
+        assert_eq!(ErrorKind::StorageFull, mock.append_text(name, String::new()).unwrap_err().kind());
+        mock.delete_text(name).unwrap();
+    }
+
+    #[test]
+    fn mock_failure_on_nth_call() {
+        let mut mock = MockTextHandler::new();
+        mock.inject_failure(
+            FailureRule::new(ErrorKind::StorageFull)
+                .on(TextOperation::Write)
+                .for_pattern("config/*.toml")
+                .on_nth_call(2));
+
+        mock.write_text(OsStr::new("config/a.toml"), String::from("1")).unwrap();
+        mock.write_text(OsStr::new("config/b.txt"), String::from("1")).unwrap();
+
+        let err = mock.write_text(OsStr::new("config/b.toml"), String::from("1")).unwrap_err();
+        assert_eq!(ErrorKind::StorageFull, err.kind());
+        assert!(!mock.text_exists(OsStr::new("config/b.toml")).unwrap());
+
+        mock.write_text(OsStr::new("config/b.toml"), String::from("1")).unwrap();
+    }
+
+    #[test]
+    fn mock_recording() {
+        let mut mock = MockTextHandler::new();
+        mock.write_text(OsStr::new("out.txt"), String::from("result")).unwrap();
+        mock.read_text(OsStr::new("out.txt")).unwrap();
+        mock.read_text(OsStr::new("in.txt")).unwrap_err();
+        mock.rename_text(OsStr::new("out.txt"), OsStr::new("done.txt")).unwrap();
+
+        assert_eq!(4, mock.call_count());
+        assert!(mock.reads_of("secrets.env").is_empty());
+
+        let writes = mock.writes_to("out.txt");
+        assert_eq!(1, writes.len());
+        assert_eq!(Some(String::from("result")), writes[0].content);
+        assert!(writes[0].succeeded());
+
+        let reads = mock.reads_of("in.txt");
+        assert_eq!(Some(ErrorKind::NotFound), reads[0].error);
+
+        let calls = mock.calls();
+        assert_eq!(TextOperation::Rename, calls[3].operation);
+        assert_eq!(Some(OsString::from("done.txt")), calls[3].target);
+
+        mock.clear_calls();
+        assert_eq!(0, mock.call_count());
+    }
+
+    #[test]
+    fn mock_recording_of_injected_failures() {
+        let mut mock = MockTextHandler::new();
+        mock.inject_failure(FailureRule::new(ErrorKind::StorageFull));
+
+        mock.append_text(OsStr::new("log.txt"), String::from("line")).unwrap_err();
+
+        let appends = mock.appends_to("log.txt");
+        assert_eq!(Some(ErrorKind::StorageFull), appends[0].error);
+        assert_eq!(Some(String::from("line")), appends[0].content);
+    }
+
+    #[test]
+    fn mock_expectations_met() {
+        let mut mock = MockTextHandler::new();
+        mock.expect_write("a.txt").with_content("alpha").times(1);
+        mock.expect_read("b.txt").returning(Ok(String::from("beta")));
+
+        mock.write_text(OsStr::new("a.txt"), String::from("alpha")).unwrap();
+        assert_eq!("beta", mock.read_text(OsStr::new("b.txt")).unwrap());
+        assert_eq!("alpha", mock.read_text(OsStr::new("a.txt")).unwrap());
+
+        mock.verify();
+    }
+
+    #[test]
+    fn mock_expectation_returning_error() {
+        let mut mock = MockTextHandler::new();
+        mock.expect_read("b.txt").returning(Err(IoError::from(ErrorKind::PermissionDenied)));
+
+        let err = mock.read_text(OsStr::new("b.txt")).unwrap_err();
+        assert_eq!(ErrorKind::PermissionDenied, err.kind());
+    }
+
+    #[test]
+    #[should_panic(expected = "expected 1 call(s) to write_text of \"a.txt\" with content \"alpha\", got 0")]
+    fn mock_expectation_unmet_on_drop() {
+        let mut mock = MockTextHandler::new();
+        mock.expect_write("a.txt").with_content("alpha").times(1);
+
+        mock.write_text(OsStr::new("a.txt"), String::from("omega")).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Content can only be expected for write_text and append_text, not for read_text.")]
+    fn mock_expectation_content_of_read() {
+        let mut mock = MockTextHandler::new();
+        mock.expect_read("b.txt").with_content("beta").times(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected call to read_text of \"c.txt\"")]
+    fn mock_strict_unexpected_call() {
+        let mut mock = MockTextHandler::new();
+        mock.set_strict(true);
+        mock.expect_read("b.txt");
+
+        let _ = mock.read_text(OsStr::new("c.txt"));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected 1 call(s) to write_text of \"a.txt\", got 2")]
+    fn mock_strict_too_many_calls() {
+        let mut mock = MockTextHandler::new();
+        mock.set_strict(true);
+        mock.expect_write("a.txt").times(1);
+
+        mock.write_text(OsStr::new("a.txt"), String::new()).unwrap();
+        mock.write_text(OsStr::new("a.txt"), String::new()).unwrap();
+    }
+
+    #[test]
+    fn mock_error_names_text() {
+        let mut mock = MockTextHandler::new();
+
+        let err = mock.rename_text(OsStr::new("old.txt"), OsStr::new("new.txt")).unwrap_err();
+        assert_eq!(ErrorKind::NotFound, err.kind());
+        assert_eq!("rename_text of \"old.txt\" to \"new.txt\" failed: entity not found", err.to_string());
+
+        mock.inject_failure(FailureRule::new(ErrorKind::StorageFull));
+        let err = mock.write_text(OsStr::new("a.txt"), String::new()).unwrap_err();
+        let text_err = TextIOError::find(&err).unwrap();
+        assert_eq!(TextOperation::Write, text_err.operation());
+        assert_eq!(OsStr::new("a.txt"), text_err.name());
+        assert_eq!(ErrorKind::StorageFull, text_err.kind());
+    }
+
+    #[test]
+    fn mock_sandboxed() {
+        let mut mock = MockTextHandler::new();
+        mock.set_sandboxed(true);
+
+        for name in ["/etc/passwd", "../secret.txt", "docs/../../secret.txt"] {
+            let err = mock.write_text(OsStr::new(name), String::new()).unwrap_err();
+            assert_eq!(ErrorKind::PermissionDenied, err.kind());
+        }
+
+        mock.write_text(OsStr::new("docs/../notes.txt"), String::from("inside")).unwrap();
+        assert_eq!("inside", mock.read_text(OsStr::new("notes.txt")).unwrap());
+
+        let err = mock.rename_text(OsStr::new("notes.txt"), OsStr::new("../notes.txt")).unwrap_err();
+        assert_eq!(ErrorKind::PermissionDenied, err.kind());
+        assert!(mock.text_exists(OsStr::new("notes.txt")).unwrap());
+    }
+
+    #[test]
+    fn mock_normalizes_names() {
+        let mut mock = MockTextHandler::new();
+        mock.write_text(OsStr::new("a/./b.txt"), String::from("one")).unwrap();
+        mock.append_text(OsStr::new("a//b.txt"), String::from(" text")).unwrap();
+
+        assert_eq!("one text", mock.read_text(OsStr::new("./a/c/../b.txt")).unwrap());
+        assert_eq!(vec!["a/b.txt"], mock.list_texts(OsStr::new("./a/")).unwrap());
+    }
+
+    #[test]
+    fn mock_normalizes_names_of_calls() {
+        let mut mock = MockTextHandler::new();
+        mock.expect_write("a/b.txt").times(1);
+        mock.inject_failure(FailureRule::new(ErrorKind::PermissionDenied).for_name("./c/d.txt"));
+        mock.inject_failure(FailureRule::new(ErrorKind::StorageFull).for_pattern("e/*.txt"));
+
+        mock.write_text(OsStr::new("./a/b.txt"), String::from("one")).unwrap();
+        assert_eq!(1, mock.writes_to("a/b.txt").len());
+        assert_eq!(OsStr::new("./a/b.txt"), mock.writes_to("a//b.txt")[0].name);
+
+        let err = mock.read_text(OsStr::new("c//d.txt")).unwrap_err();
+        assert_eq!(ErrorKind::PermissionDenied, err.kind());
+
+        let err = mock.write_text(OsStr::new("e/f/../g.txt"), String::new()).unwrap_err();
+        assert_eq!(ErrorKind::StorageFull, err.kind());
+
+        mock.verify();
+    }
+
+    #[test]
+    fn mock_without_normalization() {
+        let mut mock = MockTextHandler::new();
+        mock.set_path_normalization(false);
+        mock.write_text(OsStr::new("a/./b.txt"), String::from("one")).unwrap();
+
+        assert!(!mock.text_exists(OsStr::new("a/b.txt")).unwrap());
+        assert!(mock.text_exists(OsStr::new("a/./b.txt")).unwrap());
+    }
+
+    #[test]
+    fn mock_fs_emulation() {
+        let mut mock = MockTextHandler::new();
+        mock.set_fs_emulation(true);
+
+        let kind_of = |result: IoResult<()>| result.unwrap_err().kind();
+
+        assert_eq!(ErrorKind::NotFound, kind_of(mock.write_text(OsStr::new("a/b.txt"), String::new())));
+
+        mock.create_dir_all(OsStr::new("a/c")).unwrap();
+        mock.write_text(OsStr::new("a/b.txt"), String::from("b")).unwrap();
+        mock.write_text(OsStr::new("a/c/d.txt"), String::from("d")).unwrap();
+
+        assert_eq!(ErrorKind::IsADirectory, kind_of(mock.write_text(OsStr::new("a/c"), String::new())));
+        assert_eq!(ErrorKind::IsADirectory, mock.read_text(OsStr::new("a")).unwrap_err().kind());
+        assert_eq!(ErrorKind::NotADirectory, kind_of(mock.write_text(OsStr::new("a/b.txt/e.txt"), String::new())));
+        assert_eq!(ErrorKind::NotADirectory, kind_of(mock.create_dir_all(OsStr::new("a/b.txt/f"))));
+        assert_eq!(ErrorKind::AlreadyExists, kind_of(mock.create_dir_all(OsStr::new("a/b.txt"))));
+        assert_eq!(ErrorKind::NotFound, kind_of(mock.rename_text(OsStr::new("a/b.txt"), OsStr::new("x/b.txt"))));
+
+        assert!(!mock.text_exists(OsStr::new("a/c")).unwrap());
+        mock.rename_text(OsStr::new("a/b.txt"), OsStr::new("a/c/b.txt")).unwrap();
+        assert_eq!(vec!["a/c/b.txt", "a/c/d.txt"], mock.list_texts(OsStr::new("a")).unwrap());
+    }
+
+    #[test]
+    fn mock_fs_emulation_switched_on_later() {
+        let mut mock = MockTextHandler::new();
+        mock.write_text(OsStr::new("a/b.txt"), String::from("b")).unwrap();
+        mock.set_fs_emulation(true);
+
+        mock.write_text(OsStr::new("a/c.txt"), String::from("c")).unwrap();
+        assert_eq!(ErrorKind::IsADirectory, mock.read_text(OsStr::new("a")).unwrap_err().kind());
+    }
+
+    #[test]
+    fn mock_bytes_and_texts() {
+        let mut mock = MockTextHandler::new();
+        let invalid_utf8 = vec![0x66, 0x6f, 0x80, 0x6f];
+
+        mock.write_bytes(OsStr::new("blob.bin"), invalid_utf8.clone()).unwrap();
+        assert_eq!(ErrorKind::InvalidData, mock.read_text(OsStr::new("blob.bin")).unwrap_err().kind());
+
+        mock.append_text(OsStr::new("blob.bin"), String::from("!")).unwrap();
+        mock.copy_text(OsStr::new("blob.bin"), OsStr::new("copy.bin")).unwrap();
+        assert_eq!(b"fo\x80o!".to_vec(), mock.read_bytes(OsStr::new("copy.bin")).unwrap());
+
+        mock.write_bytes(OsStr::new("text.txt"), "héhé".as_bytes().to_vec()).unwrap();
+        assert_eq!("héhé", mock.read_text(OsStr::new("text.txt")).unwrap());
+
+        let err = mock.read_bytes(OsStr::new("missing.bin")).unwrap_err();
+        assert_eq!(ErrorKind::NotFound, err.kind());
+        assert_eq!(TextOperation::ReadBytes, mock.calls().last().unwrap().operation);
+    }
+
+    #[test]
+    fn mock_invalid_utf8() {
+        let mock = MockTextHandler::new()
+            .with_text("valid.txt", "caf\u{e9}")
+            .with_bytes("latin1.txt", b"caf\xe9".to_vec());
+
+        assert_eq!(0, mock.call_count());
+        assert_eq!("caf\u{e9}", mock.read_text_lossy(OsStr::new("valid.txt")).unwrap());
+
+        let err = mock.read_text(OsStr::new("latin1.txt")).unwrap_err();
+        assert_eq!(ErrorKind::InvalidData, err.kind());
+        assert_eq!("caf\u{FFFD}", mock.read_text_lossy(OsStr::new("latin1.txt")).unwrap());
+
+        let err = mock.read_text_lossy(OsStr::new("missing.txt")).unwrap_err();
+        assert_eq!(ErrorKind::NotFound, err.kind());
+    }
+
+    #[test]
+    fn mock_write_if_changed() {
+        let mut mock = MockTextHandler::new().with_text("a.txt", "alpha");
+        mock.inject_failure(FailureRule::new(ErrorKind::StorageFull).for_name("b.txt"));
+
+        assert!(!mock.write_text_if_changed(OsStr::new("a.txt"), String::from("alpha")).unwrap());
+        assert!(mock.write_text_if_changed(OsStr::new("a.txt"), String::from("beta")).unwrap());
+        assert!(mock.write_text_if_changed(OsStr::new("new.txt"), String::new()).unwrap());
+
+        let err = mock.write_text_if_changed(OsStr::new("b.txt"), String::new()).unwrap_err();
+        assert_eq!(ErrorKind::StorageFull, err.kind());
+
+        assert_eq!(3, mock.call_count());
+        assert_eq!("beta", mock.read_text(OsStr::new("a.txt")).unwrap());
+    }
+
+    #[test]
+    fn mock_parent_dirs() {
+        let mut mock = MockTextHandler::new().with_text("a.txt", "alpha");
+        mock.set_fs_emulation(true);
+        mock.set_parent_dirs(true);
+
+        mock.append_text(OsStr::new("b/c/d.txt"), String::from("delta")).unwrap();
+        mock.copy_text(OsStr::new("a.txt"), OsStr::new("e/f.txt")).unwrap();
+        mock.rename_text(OsStr::new("a.txt"), OsStr::new("g/a.txt")).unwrap();
+
+        let err = mock.write_text(OsStr::new("b/c/d.txt/e.txt"), String::new()).unwrap_err();
+        assert_eq!(ErrorKind::NotADirectory, err.kind());
+
+        mock.set_parent_dirs(false);
+        let err = mock.write_text(OsStr::new("h/i.txt"), String::new()).unwrap_err();
+        assert_eq!(ErrorKind::NotFound, err.kind());
+
+        assert_eq!(vec!["b/c/d.txt", "e/f.txt", "g/a.txt"], mock.list_texts(OsStr::new("")).unwrap());
+        assert_eq!(ErrorKind::IsADirectory, mock.read_text(OsStr::new("b/c")).unwrap_err().kind());
+    }
 }
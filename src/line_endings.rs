@@ -0,0 +1,255 @@
+//! Line ending conversion on top of any [`TextIOHandler`].
+
+use std::ffi::{OsStr, OsString};
+use std::io::Result as IoResult;
+use crate::TextIOHandler;
+
+/// Tells how [`LineEndingTextHandler`] converts line endings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineEndingPolicy {
+    /// Leaves line endings untouched.
+    #[default]
+    Preserve,
+    /// Converts `\r\n` line endings to `\n`.
+    Lf,
+    /// Converts `\n` line endings to `\r\n`.
+    CrLf,
+    /// When writing, converts line endings to the style of the first line ending of the existing text,
+    /// read using the wrapped handler's `peek_text` method.
+    /// Content written to a new text, to a text without line endings or to a text that can't be read
+    /// is left untouched.
+    /// When reading, this is the same as `Preserve`.
+    Detect,
+}
+
+/// LineEndingTextHandler converts the line endings of the texts read and written by another handler,
+/// according to separate [`LineEndingPolicy`]s for reading and for writing.
+/// Both policies default to `Preserve`.
+///
+/// Method `append_text` converts the content appended, leaving the existing text untouched.
+/// The other methods are passed on to the wrapped handler.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use string_io_and_mock::{LineEndingPolicy, LineEndingTextHandler, MockTextHandler, TextIOHandler};
+///
+/// let mock = MockTextHandler::new().with_text("windows.ini", "[a]\r\nb=1\r\n");
+///
+/// let mut handler = LineEndingTextHandler::new(mock)
+///     .with_read_policy(LineEndingPolicy::Lf)
+///     .with_write_policy(LineEndingPolicy::Detect);
+///
+/// let content = handler.read_text(OsStr::new("windows.ini")).unwrap();
+/// assert_eq!("[a]\nb=1\n", content);
+///
+/// handler.write_text(OsStr::new("windows.ini"), content + "c=2\n").unwrap();
+///
+/// let mock = handler.into_inner();
+/// assert_eq!("[a]\r\nb=1\r\nc=2\r\n", mock.read_text(OsStr::new("windows.ini")).unwrap());
+/// ```
+pub struct LineEndingTextHandler<H> {
+    handler: H,
+    read_policy: LineEndingPolicy,
+    write_policy: LineEndingPolicy,
+}
+
+impl<H: TextIOHandler> LineEndingTextHandler<H> {
+    pub fn new(handler: H) -> Self {
+        LineEndingTextHandler {
+            handler,
+            read_policy: LineEndingPolicy::default(),
+            write_policy: LineEndingPolicy::default(),
+        }
+    }
+
+    /// Sets the policy applied to the texts read.
+    pub fn with_read_policy(mut self, policy: LineEndingPolicy) -> Self {
+        self.read_policy = policy;
+        self
+    }
+
+    /// Sets the policy applied to the content written or appended.
+    pub fn with_write_policy(mut self, policy: LineEndingPolicy) -> Self {
+        self.write_policy = policy;
+        self
+    }
+
+    /// Returns the wrapped handler.
+    pub fn into_inner(self) -> H {
+        self.handler
+    }
+
+    /// Converts content to be written to the text with the given name.
+    fn convert_for(&self, name: &OsStr, content: String) -> String {
+        let policy = match self.write_policy {
+            LineEndingPolicy::Detect => match self.handler.peek_text(name) {
+                Ok(Some(existing)) => detect(&existing),
+                _ => LineEndingPolicy::Preserve,
+            },
+            policy => policy,
+        };
+
+        convert(content, policy)
+    }
+}
+
+/// Returns the policy matching the first line ending in `content`.
+fn detect(content: &str) -> LineEndingPolicy {
+    match content.find('\n') {
+        Some(index) if content[..index].ends_with('\r') => LineEndingPolicy::CrLf,
+        Some(_) => LineEndingPolicy::Lf,
+        None => LineEndingPolicy::Preserve,
+    }
+}
+
+fn convert(content: String, policy: LineEndingPolicy) -> String {
+    match policy {
+        LineEndingPolicy::Preserve | LineEndingPolicy::Detect => content,
+        LineEndingPolicy::Lf => content.replace("\r\n", "\n"),
+        LineEndingPolicy::CrLf => content.replace("\r\n", "\n").replace('\n', "\r\n"),
+    }
+}
+
+impl<H: TextIOHandler> TextIOHandler for LineEndingTextHandler<H> {
+
+    fn read_text(&self, name: &OsStr) -> IoResult<String> {
+        Ok(convert(self.handler.read_text(name)?, self.read_policy))
+    }
+
+    fn write_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        let content = self.convert_for(name, content);
+        self.handler.write_text(name, content)
+    }
+
+    fn append_text(&mut self, name: &OsStr, content: String) -> IoResult<()> {
+        let content = self.convert_for(name, content);
+        self.handler.append_text(name, content)
+    }
+
+    fn delete_text(&mut self, name: &OsStr) -> IoResult<()> {
+        self.handler.delete_text(name)
+    }
+
+    fn text_exists(&self, name: &OsStr) -> IoResult<bool> {
+        self.handler.text_exists(name)
+    }
+
+    fn rename_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        self.handler.rename_text(from, to)
+    }
+
+    fn copy_text(&mut self, from: &OsStr, to: &OsStr) -> IoResult<()> {
+        self.handler.copy_text(from, to)
+    }
+
+    fn list_texts(&self, prefix: &OsStr) -> IoResult<Vec<OsString>> {
+        self.handler.list_texts(prefix)
+    }
+
+    fn glob_texts(&self, pattern: &str) -> IoResult<Vec<OsString>> {
+        self.handler.glob_texts(pattern)
+    }
+
+    fn create_dir_all(&mut self, name: &OsStr) -> IoResult<()> {
+        self.handler.create_dir_all(name)
+    }
+
+    fn write_text_if_changed(&mut self, name: &OsStr, content: String) -> IoResult<bool> {
+        let content = self.convert_for(name, content);
+        self.handler.write_text_if_changed(name, content)
+    }
+
+    fn peek_text(&self, name: &OsStr) -> IoResult<Option<String>> {
+        self.handler.peek_text(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Encoding, EncodingTextHandler, MockTextHandler, TextOperation};
+
+    #[test]
+    fn conversions() {
+        assert_eq!("a\nb\nc", convert(String::from("a\r\nb\nc"), LineEndingPolicy::Lf));
+        assert_eq!("a\r\nb\r\nc", convert(String::from("a\r\nb\nc"), LineEndingPolicy::CrLf));
+        assert_eq!("a\r\nb\nc", convert(String::from("a\r\nb\nc"), LineEndingPolicy::Preserve));
+
+        assert_eq!(LineEndingPolicy::CrLf, detect("a\r\nb\n"));
+        assert_eq!(LineEndingPolicy::Lf, detect("a\nb\r\n"));
+        assert_eq!(LineEndingPolicy::Preserve, detect("a"));
+    }
+
+    #[test]
+    fn forced_policies() {
+        let mut handler = LineEndingTextHandler::new(MockTextHandler::new())
+            .with_write_policy(LineEndingPolicy::CrLf);
+
+        handler.write_text(OsStr::new("a.txt"), String::from("1\n2\n")).unwrap();
+        handler.append_text(OsStr::new("a.txt"), String::from("3\n")).unwrap();
+        assert_eq!("1\r\n2\r\n3\r\n", handler.read_text(OsStr::new("a.txt")).unwrap());
+
+        let handler = handler.with_read_policy(LineEndingPolicy::Lf);
+        assert_eq!("1\n2\n3\n", handler.read_text(OsStr::new("a.txt")).unwrap());
+    }
+
+    #[test]
+    fn detected_policy() {
+        let mock = MockTextHandler::new()
+            .with_text("unix.txt", "1\n")
+            .with_text("dos.txt", "1\r\n");
+
+        let mut handler = LineEndingTextHandler::new(mock)
+            .with_write_policy(LineEndingPolicy::Detect);
+
+        handler.write_text(OsStr::new("unix.txt"), String::from("1\r\n2\r\n")).unwrap();
+        handler.append_text(OsStr::new("dos.txt"), String::from("2\n")).unwrap();
+        handler.write_text(OsStr::new("new.txt"), String::from("1\r\n2\n")).unwrap();
+
+        assert_eq!("1\n2\n", handler.read_text(OsStr::new("unix.txt")).unwrap());
+        assert_eq!("1\r\n2\r\n", handler.read_text(OsStr::new("dos.txt")).unwrap());
+        assert_eq!("1\r\n2\n", handler.read_text(OsStr::new("new.txt")).unwrap());
+    }
+
+    #[test]
+    fn detected_policy_of_unreadable_texts() {
+        let mut mock = MockTextHandler::new()
+            .with_bytes("latin1.txt", b"caf\xe9\r\n".to_vec())
+            .with_text("dos.txt", "1\r\n");
+        mock.set_strict(true);
+        mock.expect_write("latin1.txt").times(1);
+        mock.expect_write("dos.txt").times(1);
+
+        let mut handler = LineEndingTextHandler::new(mock)
+            .with_write_policy(LineEndingPolicy::Detect);
+
+        handler.write_text(OsStr::new("latin1.txt"), String::from("1\n")).unwrap();
+        handler.write_text(OsStr::new("dos.txt"), String::from("1\n2\n")).unwrap();
+
+        let mut mock = handler.into_inner();
+        assert!(mock.reads_of("dos.txt").is_empty());
+        mock.set_strict(false);
+        assert_eq!("1\r\n", mock.read_text(OsStr::new("latin1.txt")).unwrap());
+        assert_eq!("1\r\n2\r\n", mock.read_text(OsStr::new("dos.txt")).unwrap());
+    }
+
+    #[test]
+    fn detected_policy_through_encoding_handler() {
+        let mut mock = MockTextHandler::new()
+            .with_bytes("dos.txt", vec![0xff, 0xfe, b'1', 0, b'\r', 0, b'\n', 0]);
+        mock.set_strict(true);
+        mock.expect(TextOperation::WriteBytes, "dos.txt").times(1);
+
+        let encoding_handler = EncodingTextHandler::new(mock).with_write_encoding(Encoding::Utf16Le);
+        let mut handler = LineEndingTextHandler::new(encoding_handler)
+            .with_write_policy(LineEndingPolicy::Detect);
+
+        handler.write_text(OsStr::new("dos.txt"), String::from("1\n2\n")).unwrap();
+
+        let mock = handler.into_inner().into_inner();
+        assert_eq!(1, mock.calls().len());
+        assert_eq!(
+            vec![b'1', 0, b'\r', 0, b'\n', 0, b'2', 0, b'\r', 0, b'\n', 0],
+            mock.read_bytes_stored(OsStr::new("dos.txt")).unwrap());
+    }
+}
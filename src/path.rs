@@ -0,0 +1,130 @@
+//! Resolution of text names relative to a root directory.
+
+use std::ffi::OsStr;
+use std::fs::symlink_metadata;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::path::{Component, Path, PathBuf};
+
+/// Normalizes `name` lexically the way a file system treats it, assuming all directories exist
+/// and no symbolic links are involved : `.` components and repeated or trailing separators
+/// are removed, and `..` components cancel the preceding component.
+/// Leading `..` components of relative names are kept, and `..` components directly
+/// following the root of absolute names are dropped.
+pub(crate) fn normalize(name: &OsStr) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    let mut depth = 0;
+
+    for component in Path::new(name).components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => normalized.push(component),
+            Component::CurDir => (),
+            Component::ParentDir if depth > 0 => {
+                normalized.pop();
+                depth -= 1;
+            },
+            Component::ParentDir => {
+                if !normalized.has_root() {
+                    normalized.push(component);
+                }
+            },
+            Component::Normal(part) => {
+                normalized.push(part);
+                depth += 1;
+            },
+        }
+    }
+
+    normalized
+}
+
+/// Resolves `name` lexically as a path relative to a root directory,
+/// removing `.` components and applying `..` components.
+/// Fails with [`ErrorKind::PermissionDenied`] if `name` is absolute or escapes the root.
+pub(crate) fn confine(name: &OsStr) -> IoResult<PathBuf> {
+    let mut confined = PathBuf::new();
+
+    for component in Path::new(name).components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => return Err(escape_error("is absolute")),
+            Component::CurDir => (),
+            Component::ParentDir => {
+                if !confined.pop() {
+                    return Err(escape_error("escapes the root directory"));
+                }
+            },
+            Component::Normal(part) => confined.push(part),
+        }
+    }
+
+    Ok(confined)
+}
+
+/// Resolves `name` relative to the directory `root`, like [`confine`],
+/// and also fails with [`ErrorKind::PermissionDenied`] if symbolic links lead outside of `root`.
+pub(crate) fn resolve_in_root(root: &Path, name: &OsStr) -> IoResult<PathBuf> {
+    let resolved = root.join(confine(name)?);
+    let canonical_root = root.canonicalize()?;
+
+    // The deepest existing ancestor of the resolved path tells where symbolic links lead.
+    let mut existing = resolved.as_path();
+
+    loop {
+        match existing.canonicalize() {
+            Ok(canonical) if canonical.starts_with(&canonical_root) => break,
+            Ok(_) => return Err(escape_error("leads outside of the root directory")),
+            Err(io_err) if io_err.kind() == ErrorKind::NotFound => {
+                // A dangling symbolic link could still make a write create a file outside of the root.
+                if symlink_metadata(existing).is_ok() {
+                    return Err(escape_error("is a dangling symbolic link"));
+                }
+
+                match existing.parent() {
+                    Some(parent) => existing = parent,
+                    None => break,
+                }
+            },
+            Err(io_err) => return Err(io_err),
+        }
+    }
+
+    Ok(resolved)
+}
+
+fn escape_error(reason: &str) -> IoError {
+    IoError::new(ErrorKind::PermissionDenied, format!("the name {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_names() {
+        for (name, expected) in [
+            ("a/./b.txt", "a/b.txt"),
+            ("a//b.txt", "a/b.txt"),
+            ("./a/b.txt", "a/b.txt"),
+            ("a/b/", "a/b"),
+            ("a/c/../b.txt", "a/b.txt"),
+            ("../a/../../b.txt", "../../b.txt"),
+            ("/../a/./b.txt", "/a/b.txt"),
+        ] {
+            assert_eq!(PathBuf::from(expected), normalize(OsStr::new(name)), "normalizing {}", name);
+        }
+    }
+
+    #[test]
+    fn confine_relative_names() {
+        assert_eq!(PathBuf::from("a/b.txt"), confine(OsStr::new("a/./b.txt")).unwrap());
+        assert_eq!(PathBuf::from("b.txt"), confine(OsStr::new("a/../b.txt")).unwrap());
+        assert_eq!(PathBuf::from(""), confine(OsStr::new("")).unwrap());
+    }
+
+    #[test]
+    fn confine_rejects_escapes() {
+        for name in ["/etc/passwd", "../secret.txt", "a/../../secret.txt"] {
+            let err = confine(OsStr::new(name)).unwrap_err();
+            assert_eq!(ErrorKind::PermissionDenied, err.kind());
+        }
+    }
+}
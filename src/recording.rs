@@ -0,0 +1,56 @@
+//! Call recording for [`MockTextHandler`](crate::MockTextHandler).
+
+use std::ffi::{OsStr, OsString};
+use std::io::ErrorKind;
+use crate::TextOperation;
+
+/// A TextCall describes a call made to one of the [`TextIOHandler`](crate::TextIOHandler) methods
+/// of a [`MockTextHandler`](crate::MockTextHandler).
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use string_io_and_mock::{MockTextHandler, TextIOHandler};
+///
+/// let mut mock = MockTextHandler::new();
+/// mock.write_text(OsStr::new("out.txt"), String::from("42")).unwrap();
+///
+/// assert_eq!(1, mock.writes_to("out.txt").len());
+/// assert!(mock.reads_of("secrets.env").is_empty());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextCall {
+    /// The method called.
+    pub operation: TextOperation,
+
+    /// The name of the text the call applied to. For `list_texts`, this is the prefix.
+    /// For `rename_text` and `copy_text`, this is the source name.
+    pub name: OsString,
+
+    /// The destination name of `rename_text` and `copy_text` calls.
+    pub target: Option<OsString>,
+
+    /// The content passed to `write_text` and `append_text`,
+    /// or the content returned by a successful `read_text`.
+    /// Calls to the [`BytesIOHandler`](crate::BytesIOHandler) methods don't record their content.
+    pub content: Option<String>,
+
+    /// The kind of the error returned, if the call failed.
+    pub error: Option<ErrorKind>,
+}
+
+impl TextCall {
+    pub(crate) fn new(operation: TextOperation, name: &OsStr, target: Option<&OsStr>, content: Option<String>) -> Self {
+        TextCall {
+            operation,
+            name: name.to_os_string(),
+            target: target.map(OsStr::to_os_string),
+            content,
+            error: None,
+        }
+    }
+
+    /// Returns whether the call returned `Ok`.
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
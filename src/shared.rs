@@ -0,0 +1,115 @@
+//! A [`MockTextHandler`](crate::MockTextHandler) that can be shared between components and threads.
+
+use std::ffi::{OsStr, OsString};
+use std::io::Result as IoResult;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use crate::{BytesIOHandler, MockTextHandler, TextIOHandler};
+
+/// SharedMockTextHandler gives shared access to a single [`MockTextHandler`].
+/// Cloning it is cheap, and all clones read and write the same texts.
+/// It's `Send` and `Sync`, so clones can be handed to other threads.
+///
+/// The shared mock can be configured and inspected using method `with_mock`,
+/// e.g. to inject failures or to query the calls recorded.
+/// Its expectations are verified when the last clone is dropped.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use std::thread;
+/// use string_io_and_mock::{SharedMockTextHandler, TextIOHandler};
+///
+/// let mock = SharedMockTextHandler::new();
+/// let mut writer = mock.clone();
+///
+/// thread::spawn(move || {
+///     writer.write_text(OsStr::new("report.txt"), String::from("All is well.")).unwrap();
+/// }).join().unwrap();
+///
+/// assert_eq!("All is well.", mock.read_text(OsStr::new("report.txt")).unwrap());
+/// assert_eq!(1, mock.with_mock(|inner| inner.writes_to("report.txt").len()));
+/// ```
+#[derive(Clone, Default)]
+pub struct SharedMockTextHandler {
+    mock: Arc<Mutex<MockTextHandler>>,
+}
+
+impl SharedMockTextHandler {
+    pub fn new() -> Self {
+        Self::from(MockTextHandler::new())
+    }
+
+    /// Calls `f` with exclusive access to the shared [`MockTextHandler`].
+    pub fn with_mock<R, F: FnOnce(&mut MockTextHandler) -> R>(&self, f: F) -> R {
+        f(&mut self.lock())
+    }
+
+    /// A panic while the mock is locked, e.g. by a strict mock on an unexpected call,
+    /// shouldn't make the mock unusable for other clones.
+    fn lock(&self) -> MutexGuard<'_, MockTextHandler> {
+        self.mock.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl From<MockTextHandler> for SharedMockTextHandler {
+    fn from(mock: MockTextHandler) -> Self {
+        SharedMockTextHandler {
+            mock: Arc::new(Mutex::new(mock)),
+        }
+    }
+}
+
+impl TextIOHandler for SharedMockTextHandler {
+    forward_text_io_handler!(self => self.lock(), self.lock());
+}
+
+impl BytesIOHandler for SharedMockTextHandler {
+    forward_bytes_io_handler!(self => self.lock(), self.lock());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn is_send_and_sync() {
+        assert_send_sync::<SharedMockTextHandler>();
+    }
+
+    #[test]
+    fn clones_share_texts() {
+        let mut first = SharedMockTextHandler::new();
+        let mut second = first.clone();
+
+        first.write_text(OsStr::new("a.txt"), String::from("alpha")).unwrap();
+        second.append_text(OsStr::new("a.txt"), String::from(" and omega")).unwrap();
+
+        assert_eq!("alpha and omega", first.read_text(OsStr::new("a.txt")).unwrap());
+        assert_eq!(3, second.with_mock(|mock| mock.call_count()));
+    }
+
+    #[test]
+    fn clones_across_threads() {
+        let mock = SharedMockTextHandler::new();
+
+        let handles: Vec<_> = (0..4)
+            .map(|index| {
+                let mut clone = mock.clone();
+
+                thread::spawn(move || {
+                    clone.append_text(OsStr::new("log.txt"), format!("{}", index)).unwrap();
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let mut log: Vec<char> = mock.read_text(OsStr::new("log.txt")).unwrap().chars().collect();
+        log.sort();
+        assert_eq!(vec!['0', '1', '2', '3'], log);
+    }
+}
@@ -0,0 +1,92 @@
+//! Snapshots of the texts stored by a [`MockTextHandler`].
+
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::sync::Arc;
+use crate::{MockTextHandler, TextMap};
+
+/// A MockSnapshot holds the texts, and in file system emulation mode the directories,
+/// of a [`MockTextHandler`] at the time it was taken using the mock's `snapshot` method.
+/// The mock can be rolled back to it using method `restore`, any number of times.
+///
+/// The snapshot shares its state with the mock until the mock changes,
+/// so taking a snapshot is cheap, and texts that aren't changed are never copied.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use string_io_and_mock::{MockTextHandler, TextIOHandler};
+///
+/// let mut mock = MockTextHandler::new().with_text("config.toml", "a = 1");
+/// let snapshot = mock.snapshot();
+///
+/// mock.write_text(OsStr::new("config.toml"), String::from("a = 2")).unwrap();
+/// mock.write_text(OsStr::new("cache.bin"), String::new()).unwrap();
+///
+/// mock.restore(&snapshot);
+/// assert_eq!("a = 1", mock.read_text(OsStr::new("config.toml")).unwrap());
+/// assert!(!mock.text_exists(OsStr::new("cache.bin")).unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct MockSnapshot {
+    pub(crate) texts: Arc<TextMap>,
+    pub(crate) dirs: Arc<HashSet<OsString>>,
+}
+
+impl MockTextHandler {
+    /// Returns a snapshot of the texts and directories stored by the mock. See [`MockSnapshot`].
+    /// Recorded calls, failure rules, expectations and settings aren't part of the snapshot.
+    pub fn snapshot(&self) -> MockSnapshot {
+        MockSnapshot {
+            texts: Arc::clone(&self.texts),
+            dirs: Arc::clone(&self.dirs),
+        }
+    }
+
+    /// Replaces the texts and directories stored by the mock by those of the given snapshot.
+    /// Restoring isn't recorded as a call to the mock.
+    pub fn restore(&mut self, snapshot: &MockSnapshot) {
+        self.texts = Arc::clone(&snapshot.texts);
+        self.dirs = Arc::clone(&snapshot.dirs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+    use super::*;
+    use crate::TextIOHandler;
+
+    #[test]
+    fn snapshots_share_unchanged_texts() {
+        let mut mock = MockTextHandler::new()
+            .with_text("a.txt", "alpha")
+            .with_text("b.txt", "beta");
+
+        let snapshot = mock.snapshot();
+        assert!(Arc::ptr_eq(&snapshot.texts, &mock.texts));
+
+        mock.append_text(OsStr::new("b.txt"), String::from("!")).unwrap();
+        assert!(!Arc::ptr_eq(&snapshot.texts, &mock.texts));
+        assert!(Arc::ptr_eq(&snapshot.texts[OsStr::new("a.txt")], &mock.texts[OsStr::new("a.txt")]));
+        assert_eq!(b"beta".to_vec(), *snapshot.texts[OsStr::new("b.txt")]);
+    }
+
+    #[test]
+    fn restore_repeatedly() {
+        let mut mock = MockTextHandler::new();
+        mock.set_fs_emulation(true);
+        mock.create_dir_all(OsStr::new("out")).unwrap();
+        let snapshot = mock.snapshot();
+
+        for step in 0..3 {
+            mock.write_text(OsStr::new("out/step.txt"), step.to_string()).unwrap();
+            mock.create_dir_all(OsStr::new("tmp")).unwrap();
+            mock.restore(&snapshot);
+
+            assert!(mock.list_texts(OsStr::new("")).unwrap().is_empty());
+            assert!(mock.write_text(OsStr::new("tmp/a.txt"), String::new()).is_err());
+        }
+
+        assert_eq!(13, mock.call_count());
+    }
+}
@@ -0,0 +1,207 @@
+//! The txtar archive format, holding several texts in a single text.
+
+use std::ffi::OsStr;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use crate::MockTextHandler;
+
+/// A Txtar is an archive of texts in the txtar format known from Go,
+/// which keeps a set of texts readable and reviewable within a single text, e.g. in a test :
+/// an optional comment is followed by the texts, each preceded by a `-- name --` marker line.
+///
+/// When formatted, a newline is added to comments and texts that don't end with one,
+/// so parsing a formatted archive only yields the same archive if they all do.
+/// # Examples
+/// ```
+/// use std::ffi::OsStr;
+/// use string_io_and_mock::{MockTextHandler, TextIOHandler, Txtar};
+///
+/// let archive = "\
+/// Renaming the settings file.
+/// -- input/settings.ini --
+/// [a]
+/// b=1
+/// -- expected/config.ini --
+/// [a]
+/// b=1
+/// ";
+///
+/// let txtar = Txtar::parse(archive);
+/// assert_eq!("Renaming the settings file.\n", txtar.comment);
+/// assert_eq!(2, txtar.files.len());
+/// assert_eq!(archive, txtar.to_string());
+///
+/// let mut mock = MockTextHandler::from_txtar(archive);
+/// mock.rename_text(OsStr::new("input/settings.ini"), OsStr::new("input/config.ini")).unwrap();
+///
+/// assert_eq!(
+///     mock.read_text(OsStr::new("expected/config.ini")).unwrap(),
+///     mock.read_text(OsStr::new("input/config.ini")).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Txtar {
+    pub comment: String,
+    /// The names and contents of the texts, in archive order.
+    pub files: Vec<(String, String)>,
+}
+
+impl Txtar {
+    /// Parses an archive. Any text is a valid archive, as lines that aren't markers
+    /// belong to the comment or to the preceding text.
+    pub fn parse(archive: &str) -> Self {
+        let mut txtar = Txtar::default();
+
+        for line in archive.split_inclusive('\n') {
+            match marker_name(line) {
+                Some(name) => txtar.files.push((name.to_string(), String::new())),
+                None => match txtar.files.last_mut() {
+                    Some((_, content)) => content.push_str(line),
+                    None => txtar.comment.push_str(line),
+                },
+            }
+        }
+
+        txtar
+    }
+}
+
+/// Returns the name in a `-- name --` marker line, if `line` is one.
+fn marker_name(line: &str) -> Option<&str> {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    let line = line.strip_suffix('\r').unwrap_or(line);
+
+    line.strip_prefix("-- ")?
+        .strip_suffix(" --")
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+impl Display for Txtar {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write_with_newline(f, &self.comment)?;
+
+        for (name, content) in &self.files {
+            writeln!(f, "-- {} --", name)?;
+            write_with_newline(f, content)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_with_newline(f: &mut Formatter<'_>, content: &str) -> FmtResult {
+    write!(f, "{}", content)?;
+
+    match content.is_empty() || content.ends_with('\n') {
+        true => Ok(()),
+        false => writeln!(f),
+    }
+}
+
+impl MockTextHandler {
+    /// Creates a mock holding the texts of a txtar archive. The archive's comment is ignored.
+    /// See [`Txtar`].
+    pub fn from_txtar(archive: &str) -> Self {
+        Txtar::parse(archive)
+            .files
+            .into_iter()
+            .fold(MockTextHandler::new(), |mock, (name, content)| mock.with_text(name, content))
+    }
+
+    /// Returns a txtar archive holding all texts of the mock, sorted by name. See [`Txtar`].
+    /// Fails with [`ErrorKind::InvalidData`] if a name or a content isn't valid UTF-8,
+    /// or if parsing the archive wouldn't yield the same texts : if a name doesn't fit in a marker line,
+    /// or if a content holds a marker line or doesn't end with a newline.
+    /// Creating the archive isn't recorded as calls to the mock.
+    pub fn to_txtar(&self) -> IoResult<String> {
+        let mut names: Vec<&OsStr> = self.texts.keys().map(|name| name.as_os_str()).collect();
+        names.sort();
+
+        let mut txtar = Txtar::default();
+
+        for name in names {
+            let invalid = |reason: &str| IoError::new(ErrorKind::InvalidData, format!("{:?} {}", name, reason));
+            let utf8_name = name.to_str().ok_or_else(|| invalid("isn't valid UTF-8"))?;
+            let content = String::from_utf8(self.texts[name].to_vec()).map_err(|_| invalid("isn't valid UTF-8"))?;
+
+            if utf8_name.contains(['\n', '\r']) || marker_name(&format!("-- {} --", utf8_name)) != Some(utf8_name) {
+                return Err(invalid("can't be a txtar marker name"));
+            }
+
+            if content.split_inclusive('\n').any(|line| marker_name(line).is_some()) {
+                return Err(invalid("holds a txtar marker line"));
+            }
+
+            if !content.is_empty() && !content.ends_with('\n') {
+                return Err(invalid("doesn't end with a newline"));
+            }
+
+            txtar.files.push((utf8_name.to_string(), content));
+        }
+
+        Ok(txtar.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BytesIOHandler;
+
+    #[test]
+    fn parse_archive() {
+        let txtar = Txtar::parse("-- a.txt --\nalpha\n--  b.txt  --\n-- not a marker\n-- --\n-- c.txt --");
+
+        assert_eq!("", txtar.comment);
+        assert_eq!(
+            vec![
+                (String::from("a.txt"), String::from("alpha\n")),
+                (String::from("b.txt"), String::from("-- not a marker\n-- --\n")),
+                (String::from("c.txt"), String::new()),
+            ],
+            txtar.files);
+    }
+
+    #[test]
+    fn format_archive() {
+        let txtar = Txtar {
+            comment: String::from("comment"),
+            files: vec![(String::from("a.txt"), String::from("alpha")), (String::from("b.txt"), String::new())],
+        };
+
+        assert_eq!("comment\n-- a.txt --\nalpha\n-- b.txt --\n", txtar.to_string());
+    }
+
+    #[test]
+    fn mock_round_trip() {
+        let archive = "-- a.txt --\nalpha\n-- docs/b.md --\n# Beta\n";
+        let mut mock = MockTextHandler::from_txtar(archive);
+
+        assert_eq!(archive, mock.to_txtar().unwrap());
+        assert_eq!(0, mock.call_count());
+
+        mock.write_bytes(OsStr::new("c.bin"), vec![0xff]).unwrap();
+        assert_eq!(ErrorKind::InvalidData, mock.to_txtar().unwrap_err().kind());
+    }
+
+    #[test]
+    fn mock_round_trip_of_unfit_texts() {
+        let unfit = [
+            ("nested.txt", "alpha\n-- b.txt --\nbeta\n"),
+            ("unterminated.txt", "alpha"),
+            (" padded.txt", "alpha\n"),
+            ("two\nlines.txt", "alpha\n"),
+        ];
+
+        for (name, content) in unfit {
+            let mock = MockTextHandler::new().with_text("a.txt", "fit\n").with_text(name, content);
+
+            assert_eq!(ErrorKind::InvalidData, mock.to_txtar().unwrap_err().kind(), "{:?}", name);
+        }
+
+        let mock = MockTextHandler::new().with_text("a.txt", "-- not a marker\n").with_text("b.txt", "");
+        let archive = mock.to_txtar().unwrap();
+        assert_eq!("-- a.txt --\n-- not a marker\n-- b.txt --\n", archive);
+        assert_eq!(archive, MockTextHandler::from_txtar(&archive).to_txtar().unwrap());
+    }
+}
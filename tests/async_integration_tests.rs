@@ -0,0 +1,59 @@
+#![cfg(feature = "async")]
+
+use std::ffi::OsString;
+use std::io::ErrorKind;
+use serial_test::file_serial;
+use string_io_and_mock::{AsyncFileTextHandler, AsyncTextIOHandler, FileTextHandler, SyncToAsyncAdapter};
+
+mod utils;
+
+#[tokio::test]
+#[file_serial]
+async fn async_read_write_append() {
+    let playground_name = utils::ensure_playground(true);
+    let mut file_name = playground_name.clone();
+    file_name.push(OsString::from("/Journal.txt"));
+
+    let mut fth = AsyncFileTextHandler::new();
+    fth.write_text(&file_name, String::from("Monday: rain.\n")).await.unwrap();
+    fth.append_text(&file_name, String::from("Tuesday: more rain.\n")).await.unwrap();
+
+    let read_back = fth.read_text(&file_name).await.unwrap();
+    assert_eq!("Monday: rain.\nTuesday: more rain.\n", read_back);
+
+    assert_eq!(vec![file_name.clone()], fth.list_texts(&playground_name).await.unwrap());
+
+    fth.delete_text(&file_name).await.unwrap();
+    let err = fth.read_text(&file_name).await.unwrap_err();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+}
+
+#[tokio::test]
+#[file_serial]
+async fn async_adapter_over_file_handler() {
+    let playground_name = utils::ensure_playground(true);
+    let mut file_name = playground_name.clone();
+    file_name.push(OsString::from("/TheWell.txt"));
+
+    let mut handler = SyncToAsyncAdapter::new(FileTextHandler::new());
+    handler.write_text(&file_name, String::from("One can move the city, but not the well.")).await.unwrap();
+
+    let read_back = AsyncFileTextHandler::new().read_text(&file_name).await.unwrap();
+    assert_eq!("One can move the city, but not the well.", read_back);
+}
+
+#[tokio::test]
+#[file_serial]
+async fn async_copy_onto_itself() {
+    let playground_name = utils::ensure_playground(true);
+    let mut file_name = playground_name.clone();
+    file_name.push(OsString::from("/a.txt"));
+    let mut same_file_name = playground_name.clone();
+    same_file_name.push(OsString::from("/./a.txt"));
+
+    let mut fth = AsyncFileTextHandler::new();
+    fth.write_text(&file_name, String::from("Still here.")).await.unwrap();
+    fth.copy_text(&file_name, &same_file_name).await.unwrap();
+
+    assert_eq!("Still here.", fth.read_text(&file_name).await.unwrap());
+}
@@ -1,12 +1,20 @@
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
+use std::fs::{create_dir_all, metadata};
 use std::io::ErrorKind;
+use std::path::Path;
+use std::thread::sleep;
+use std::time::Duration;
 use serial_test::file_serial;
-use string_io_and_mock::{TextIOHandler, FileTextHandler};
+use string_io_and_mock::{
+    diff_handlers, BomPolicy, BytesIOHandler, Encoding, EncodingTextHandler, FixtureLoader, LineEndingPolicy,
+    LineEndingTextHandler, MockTextHandler, TextIOHandler, TextIOError, TextOperation, FileTextHandler, WriteMode,
+};
 
 mod utils;
 
 #[test]
 #[file_serial]
+#[allow(clippy::needless_borrows_for_generic_args)]
 fn overwrite() {
     let playground_name = utils::ensure_playground(true);
     let mut file_name = playground_name.clone();
@@ -26,6 +34,7 @@ fn overwrite() {
 
 #[test]
 #[file_serial]
+#[allow(clippy::needless_borrows_for_generic_args)]
 fn read_and_write() {
     let playground_name = utils::ensure_playground(true);
     let mut file_name = playground_name.clone();
@@ -63,6 +72,72 @@ through the air was rising.
 
 #[test]
 #[file_serial]
+fn append() {
+    let playground_name = utils::ensure_playground(true);
+    let mut file_name = playground_name.clone();
+    file_name.push(OsString::from("/Journal.txt"));
+
+    let mut fth = FileTextHandler::new();
+    fth.append_text(&file_name, String::from("Monday: rain.\n")).unwrap();
+    fth.append_text(&file_name, String::from("Tuesday: more rain.\n")).unwrap();
+
+    let read_back = fth.read_text(&file_name).unwrap();
+
+    assert_eq!("Monday: rain.\nTuesday: more rain.\n", read_back);
+}
+
+#[test]
+#[file_serial]
+fn atomic_overwrite() {
+    let playground_name = utils::ensure_playground(true);
+    let mut file_name = playground_name.clone();
+    file_name.push(OsString::from("/TheWell.txt"));
+
+    let txt1 = String::from("Well, about the well :");
+    let txt2 = String::from("One can move the city, but not the well.");
+
+    for write_mode in [WriteMode::Atomic, WriteMode::AtomicSynced] {
+        let mut fth = FileTextHandler::new().with_write_mode(write_mode);
+        fth.write_text(&file_name, txt1.clone()).unwrap();
+        fth.write_text(&file_name, txt2.clone()).unwrap();
+
+        assert_eq!(txt2, fth.read_text(&file_name).unwrap());
+    }
+
+    // No temporary files should be left behind.
+    let names = FileTextHandler::new().list_texts(&playground_name).unwrap();
+    assert_eq!(vec![file_name], names);
+}
+
+#[cfg(unix)]
+#[test]
+#[file_serial]
+fn atomic_overwrite_through_symlink() {
+    use std::fs::{set_permissions, symlink_metadata, Permissions};
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    let playground_name = utils::ensure_playground(true);
+    let playground = Path::new(&playground_name);
+    create_dir_all(playground.join("real")).unwrap();
+
+    let target = playground.join("real/TheWell.txt");
+    let link = playground.join("TheWell.txt");
+    symlink("real/TheWell.txt", &link).unwrap();
+
+    let mut fth = FileTextHandler::new().with_write_mode(WriteMode::Atomic);
+    fth.write_text(link.as_os_str(), String::from("Well, about the well :")).unwrap();
+    set_permissions(&target, Permissions::from_mode(0o640)).unwrap();
+
+    fth.write_text(link.as_os_str(), String::from("One can move the city, but not the well.")).unwrap();
+
+    assert!(symlink_metadata(&link).unwrap().file_type().is_symlink());
+    assert_eq!("One can move the city, but not the well.", fth.read_text(target.as_os_str()).unwrap());
+    assert_eq!(0o640, metadata(&target).unwrap().permissions().mode() & 0o777);
+}
+
+#[test]
+#[file_serial]
+#[allow(clippy::needless_borrows_for_generic_args)]
 fn read_missing() {
     let playground_name = utils::ensure_playground(true);
     let mut file_name = playground_name.clone();
@@ -74,6 +149,465 @@ fn read_missing() {
         Ok(_) => panic!("Method read_text should return an Err if no text with the passed name is found."),
         Err(err) => {
             assert_eq!(ErrorKind::NotFound, err.kind());
+
+            let text_err = TextIOError::find(&err).unwrap();
+            assert_eq!(TextOperation::Read, text_err.operation());
+            assert_eq!(file_name, text_err.name());
+            assert!(err.to_string().contains("missing.txt"));
         },
     }
 }
+
+#[test]
+#[file_serial]
+fn delete() {
+    let playground_name = utils::ensure_playground(true);
+    let mut file_name = playground_name.clone();
+    file_name.push(OsString::from("/Ephemeral.txt"));
+
+    let mut fth = FileTextHandler::new();
+    fth.write_text(&file_name, String::from("Here today, gone tomorrow.")).unwrap();
+    assert!(fth.text_exists(&file_name).unwrap());
+
+    fth.delete_text(&file_name).unwrap();
+    assert!(!fth.text_exists(&file_name).unwrap());
+
+    let err = fth.delete_text(&file_name).unwrap_err();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+}
+
+#[test]
+#[file_serial]
+fn rename() {
+    let playground_name = utils::ensure_playground(true);
+    let mut from = playground_name.clone();
+    from.push(OsString::from("/Montague.txt"));
+    let mut to = playground_name.clone();
+    to.push(OsString::from("/Capulet.txt"));
+
+    let txt = String::from("A rose by any other name would smell as sweet.");
+    let mut fth = FileTextHandler::new();
+    fth.write_text(&from, txt.clone()).unwrap();
+
+    fth.rename_text(&from, &to).unwrap();
+
+    assert!(!fth.text_exists(&from).unwrap());
+    assert_eq!(txt, fth.read_text(&to).unwrap());
+
+    let err = fth.rename_text(&from, &to).unwrap_err();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+}
+
+#[test]
+#[file_serial]
+fn copy() {
+    let playground_name = utils::ensure_playground(true);
+    let mut from = playground_name.clone();
+    from.push(OsString::from("/Original.txt"));
+    let mut to = playground_name.clone();
+    to.push(OsString::from("/Copy.txt"));
+    let mut missing = playground_name.clone();
+    missing.push(OsString::from("/missing.txt"));
+
+    let txt = String::from("Imitation is the sincerest form of flattery.");
+    let mut fth = FileTextHandler::new();
+    fth.write_text(&from, txt.clone()).unwrap();
+
+    fth.copy_text(&from, &to).unwrap();
+
+    assert_eq!(txt, fth.read_text(&from).unwrap());
+    assert_eq!(txt, fth.read_text(&to).unwrap());
+
+    let mut same = playground_name.clone();
+    same.push(OsString::from("/./Original.txt"));
+    fth.copy_text(&from, &same).unwrap();
+    assert_eq!(txt, fth.read_text(&from).unwrap());
+
+    let err = fth.copy_text(&missing, &to).unwrap_err();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+}
+
+#[cfg(unix)]
+#[test]
+#[file_serial]
+fn copy_onto_hard_link() {
+    use std::fs::hard_link;
+
+    let playground_name = utils::ensure_playground(true);
+    let playground = Path::new(&playground_name);
+    let (from, to) = (playground.join("Original.txt"), playground.join("Link.txt"));
+
+    let txt = String::from("Imitation is the sincerest form of flattery.");
+    let mut fth = FileTextHandler::new();
+    fth.write_text(from.as_os_str(), txt.clone()).unwrap();
+    hard_link(&from, &to).unwrap();
+
+    fth.copy_text(from.as_os_str(), to.as_os_str()).unwrap();
+    assert_eq!(txt, fth.read_text(to.as_os_str()).unwrap());
+}
+
+#[test]
+#[file_serial]
+fn list_and_glob() {
+    let playground_name = utils::ensure_playground(true);
+    let playground = Path::new(&playground_name);
+    create_dir_all(playground.join("docs/api")).unwrap();
+    create_dir_all(playground.join("docs_old")).unwrap();
+
+    let mut fth = FileTextHandler::new();
+
+    for name in ["docs/intro.md", "docs/api/calls.md", "docs/api/notes.txt", "docs_old/intro.md"] {
+        fth.write_text(playground.join(name).as_os_str(), String::from(name)).unwrap();
+    }
+
+    let listed = fth.list_texts(playground.join("docs").as_os_str()).unwrap();
+    let expected: Vec<OsString> = ["docs/api/calls.md", "docs/api/notes.txt", "docs/intro.md"]
+        .iter()
+        .map(|name| playground.join(name).into_os_string())
+        .collect();
+    assert_eq!(expected, listed);
+
+    let missing = fth.list_texts(playground.join("nowhere").as_os_str()).unwrap();
+    assert!(missing.is_empty());
+
+    // Glob patterns ignore "." components, so the names found don't start with "./".
+    let globbed = fth.glob_texts("./tests/playground/docs/**/*.md").unwrap();
+    let expected: Vec<OsString> = ["docs/api/calls.md", "docs/intro.md"]
+        .iter()
+        .map(|name| Path::new("tests/playground").join(name).into_os_string())
+        .collect();
+    assert_eq!(expected, globbed);
+
+    let absolute = playground.canonicalize().unwrap();
+    let globbed = fth.glob_texts(&format!("{}/docs/*.md", absolute.to_str().unwrap())).unwrap();
+    assert_eq!(vec![absolute.join("docs/intro.md").into_os_string()], globbed);
+}
+
+#[cfg(unix)]
+#[test]
+#[file_serial]
+fn glob_skips_unmatched_dirs() {
+    use std::fs::{set_permissions, Permissions};
+    use std::os::unix::fs::PermissionsExt;
+
+    let playground_name = utils::ensure_playground(true);
+    let playground = Path::new(&playground_name);
+    let private = playground.join("docs/private");
+    create_dir_all(&private).unwrap();
+
+    let mut fth = FileTextHandler::new();
+    fth.write_text(playground.join("docs/intro.md").as_os_str(), String::from("public")).unwrap();
+    fth.write_text(private.join("secret.md").as_os_str(), String::from("private")).unwrap();
+
+    // The pattern has no "**", so the unreadable directory below its depth is never read.
+    set_permissions(&private, Permissions::from_mode(0o000)).unwrap();
+    let globbed = fth.glob_texts("tests/playground/docs/*.md");
+    set_permissions(&private, Permissions::from_mode(0o755)).unwrap();
+
+    assert_eq!(vec![OsString::from("tests/playground/docs/intro.md")], globbed.unwrap());
+}
+
+#[test]
+#[file_serial]
+fn rooted() {
+    let playground_name = utils::ensure_playground(true);
+    let playground = Path::new(&playground_name);
+    let root = playground.join("root");
+    create_dir_all(root.join("docs")).unwrap();
+
+    let mut fth = FileTextHandler::new().with_root(&root);
+    fth.write_text(OsStr::new("docs/intro.md"), String::from("inside")).unwrap();
+
+    assert_eq!("inside", FileTextHandler::new().read_text(root.join("docs/intro.md").as_os_str()).unwrap());
+    assert_eq!("inside", fth.read_text(OsStr::new("docs/../docs/intro.md")).unwrap());
+    assert_eq!(vec![OsString::from("docs/intro.md")], fth.list_texts(OsStr::new("")).unwrap());
+
+    FileTextHandler::new().write_text(playground.join("outside.txt").as_os_str(), String::from("outside")).unwrap();
+    let absolute_outside = playground.join("outside.txt").canonicalize().unwrap();
+
+    for name in [OsStr::new("../outside.txt"), OsStr::new("docs/../../outside.txt"), absolute_outside.as_os_str()] {
+        let err = fth.read_text(name).unwrap_err();
+        assert_eq!(ErrorKind::PermissionDenied, err.kind());
+    }
+
+    let err = fth.rename_text(OsStr::new("docs/intro.md"), OsStr::new("../intro.md")).unwrap_err();
+    assert_eq!(ErrorKind::PermissionDenied, err.kind());
+}
+
+#[cfg(unix)]
+#[test]
+#[file_serial]
+fn rooted_symlink_escape() {
+    use std::os::unix::fs::symlink;
+
+    let playground_name = utils::ensure_playground(true);
+    let playground = Path::new(&playground_name);
+    let root = playground.join("root");
+    create_dir_all(&root).unwrap();
+    create_dir_all(playground.join("elsewhere")).unwrap();
+
+    let outside = playground.join("elsewhere").canonicalize().unwrap();
+    symlink(&outside, root.join("link")).unwrap();
+    symlink(outside.join("missing.txt"), root.join("dangling.txt")).unwrap();
+
+    let mut fth = FileTextHandler::new().with_root(&root);
+
+    let err = fth.write_text(OsStr::new("link/escaped.txt"), String::new()).unwrap_err();
+    assert_eq!(ErrorKind::PermissionDenied, err.kind());
+
+    let err = fth.write_text(OsStr::new("dangling.txt"), String::new()).unwrap_err();
+    assert_eq!(ErrorKind::PermissionDenied, err.kind());
+
+    assert!(!outside.join("escaped.txt").exists());
+    assert!(!outside.join("missing.txt").exists());
+}
+
+#[test]
+#[file_serial]
+fn directories() {
+    let playground_name = utils::ensure_playground(true);
+    let playground = Path::new(&playground_name);
+    let mut fth = FileTextHandler::new();
+
+    let err = fth.write_text(playground.join("a/b.txt").as_os_str(), String::new()).unwrap_err();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+
+    fth.create_dir_all(playground.join("a/c").as_os_str()).unwrap();
+    fth.write_text(playground.join("a/b.txt").as_os_str(), String::from("b")).unwrap();
+
+    let err = fth.read_text(playground.join("a/c").as_os_str()).unwrap_err();
+    assert_eq!(ErrorKind::IsADirectory, err.kind());
+
+    let err = fth.write_text(playground.join("a/b.txt/e.txt").as_os_str(), String::new()).unwrap_err();
+    assert_eq!(ErrorKind::NotADirectory, err.kind());
+
+    assert!(!fth.text_exists(playground.join("a/c").as_os_str()).unwrap());
+}
+
+#[test]
+#[file_serial]
+fn bytes_and_texts() {
+    let playground_name = utils::ensure_playground(true);
+    let mut file_name = playground_name.clone();
+    file_name.push(OsString::from("/blob.bin"));
+
+    let mut fth = FileTextHandler::new();
+    fth.write_bytes(&file_name, vec![0x66, 0x6f, 0x80, 0x6f]).unwrap();
+
+    let err = fth.read_text(&file_name).unwrap_err();
+    assert_eq!(ErrorKind::InvalidData, err.kind());
+
+    fth.write_text(&file_name, String::from("héhé")).unwrap();
+    assert_eq!("héhé".as_bytes().to_vec(), fth.read_bytes(&file_name).unwrap());
+}
+
+#[test]
+#[file_serial]
+fn read_lossy() {
+    let playground_name = utils::ensure_playground(true);
+    let mut file_name = playground_name.clone();
+    file_name.push(OsString::from("/latin1.txt"));
+
+    let mut fth = FileTextHandler::new();
+    fth.write_bytes(&file_name, b"caf\xe9".to_vec()).unwrap();
+
+    let err = fth.read_text(&file_name).unwrap_err();
+    assert_eq!(ErrorKind::InvalidData, err.kind());
+    assert_eq!("caf\u{FFFD}", fth.read_text_lossy(&file_name).unwrap());
+}
+
+#[test]
+#[file_serial]
+fn encodings() {
+    let playground_name = utils::ensure_playground(true);
+    let mut file_name = playground_name.clone();
+    file_name.push(OsString::from("/utf16.csv"));
+
+    let mut handler = EncodingTextHandler::new(FileTextHandler::new())
+        .with_write_encoding(Encoding::Utf16Le)
+        .with_bom_policy(BomPolicy::Always);
+
+    handler.write_text(&file_name, String::from("naïve;€")).unwrap();
+    assert_eq!("naïve;€", handler.read_text(&file_name).unwrap());
+
+    let fth = handler.into_inner();
+    let content = fth.read_bytes(&file_name).unwrap();
+    assert_eq!(vec![0xff, 0xfe, b'n', 0], content[..4].to_vec());
+    assert_eq!(ErrorKind::InvalidData, fth.read_text(&file_name).unwrap_err().kind());
+}
+
+#[test]
+#[file_serial]
+fn line_endings() {
+    let playground_name = utils::ensure_playground(true);
+    let mut dos_name = playground_name.clone();
+    dos_name.push(OsString::from("/dos.txt"));
+    let mut unix_name = playground_name.clone();
+    unix_name.push(OsString::from("/unix.txt"));
+
+    let mut fth = FileTextHandler::new();
+    fth.write_text(&dos_name, String::from("one\r\ntwo\r\n")).unwrap();
+    fth.write_text(&unix_name, String::from("one\ntwo\n")).unwrap();
+
+    let mut handler = LineEndingTextHandler::new(fth)
+        .with_read_policy(LineEndingPolicy::Lf)
+        .with_write_policy(LineEndingPolicy::Detect);
+
+    for name in [&dos_name, &unix_name] {
+        let content = handler.read_text(name).unwrap();
+        assert_eq!("one\ntwo\n", content);
+        handler.write_text(name, content + "three\n").unwrap();
+    }
+
+    let fth = handler.into_inner();
+    assert_eq!("one\r\ntwo\r\nthree\r\n", fth.read_text(&dos_name).unwrap());
+    assert_eq!("one\ntwo\nthree\n", fth.read_text(&unix_name).unwrap());
+}
+
+#[test]
+#[file_serial]
+fn write_if_changed() {
+    let playground_name = utils::ensure_playground(true);
+    let mut file_name = playground_name.clone();
+    file_name.push(OsString::from("/generated.rs"));
+
+    let mut fth = FileTextHandler::new();
+    assert!(fth.write_text_if_changed(&file_name, String::from("fn a() {}")).unwrap());
+    let modified = metadata(&file_name).unwrap().modified().unwrap();
+
+    sleep(Duration::from_millis(20));
+    assert!(!fth.write_text_if_changed(&file_name, String::from("fn a() {}")).unwrap());
+    assert_eq!(modified, metadata(&file_name).unwrap().modified().unwrap());
+
+    assert!(fth.write_text_if_changed(&file_name, String::from("fn b() {}")).unwrap());
+    assert_eq!("fn b() {}", fth.read_text(&file_name).unwrap());
+}
+
+#[test]
+#[file_serial]
+fn parent_dirs() {
+    let playground_name = utils::ensure_playground(true);
+    let playground = Path::new(&playground_name);
+    let name = playground.join("out/2024/report.txt");
+
+    let mut fth = FileTextHandler::new();
+    let err = fth.write_text(name.as_os_str(), String::from("Done.")).unwrap_err();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+
+    let mut fth = FileTextHandler::new().with_parent_dirs(true);
+    fth.write_text(name.as_os_str(), String::from("Done.")).unwrap();
+    fth.copy_text(name.as_os_str(), playground.join("copies/report.txt").as_os_str()).unwrap();
+
+    assert_eq!("Done.", fth.read_text(playground.join("copies/report.txt").as_os_str()).unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+#[file_serial]
+fn parent_dir_mode() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let playground_name = utils::ensure_playground(true);
+    let playground = Path::new(&playground_name);
+
+    let mut fth = FileTextHandler::new().with_parent_dirs(true).with_dir_mode(0o700);
+    fth.write_text(playground.join("private/notes.txt").as_os_str(), String::new()).unwrap();
+
+    let mode = metadata(playground.join("private")).unwrap().permissions().mode();
+    assert_eq!(0o700, mode & 0o777);
+}
+
+#[test]
+#[file_serial]
+fn load_fixture() {
+    let playground_name = utils::ensure_playground(true);
+    let playground = Path::new(&playground_name);
+
+    let mut fth = FileTextHandler::new().with_parent_dirs(true);
+    fth.write_text(playground.join("fixture/config/app.toml").as_os_str(), String::from("a = 1")).unwrap();
+    fth.write_text(playground.join("fixture/config/old.toml").as_os_str(), String::from("a = 0")).unwrap();
+    fth.write_bytes(playground.join("fixture/logo.png").as_os_str(), vec![0x89, 0x50]).unwrap();
+
+    let mock = MockTextHandler::from_dir(playground.join("fixture")).unwrap();
+    assert_eq!(0, mock.call_count());
+    assert_eq!(
+        vec!["config/app.toml", "config/old.toml", "logo.png"],
+        mock.list_texts(OsStr::new("")).unwrap());
+    assert_eq!(vec![0x89, 0x50], mock.read_bytes(OsStr::new("logo.png")).unwrap());
+
+    let mock = FixtureLoader::new(playground.join("fixture"))
+        .with_absolute_keys(true)
+        .include("config/*.toml")
+        .exclude("**/old.*")
+        .load()
+        .unwrap();
+    let absolute_name = playground.join("fixture/config/app.toml").canonicalize().unwrap();
+    assert_eq!(vec![absolute_name.clone()], mock.list_texts(OsStr::new("/")).unwrap());
+    assert_eq!("a = 1", mock.read_text(absolute_name.as_os_str()).unwrap());
+
+    let err = MockTextHandler::from_dir(playground.join("missing")).err().unwrap();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+}
+
+#[test]
+#[file_serial]
+fn export_mock() {
+    let playground_name = utils::ensure_playground(true);
+    let playground = Path::new(&playground_name);
+
+    let mut mock = MockTextHandler::new().with_text("/abs/a.txt", "alpha");
+    mock.set_fs_emulation(true);
+    mock.create_dir_all(OsStr::new("empty")).unwrap();
+    mock.create_dir_all(OsStr::new("out/2024")).unwrap();
+    mock.write_bytes(OsStr::new("out/2024/logo.png"), vec![0x89, 0x50]).unwrap();
+
+    mock.export_to_dir(playground.join("export")).unwrap();
+
+    let fth = FileTextHandler::new().with_root(playground.join("export"));
+    assert_eq!(vec!["abs/a.txt", "out/2024/logo.png"], fth.list_texts(OsStr::new("")).unwrap());
+    assert_eq!(vec![0x89, 0x50], fth.read_bytes(OsStr::new("out/2024/logo.png")).unwrap());
+    assert!(playground.join("export/empty").is_dir());
+
+    let mock = MockTextHandler::new().with_text("/a.txt", "absolute").with_text("a.txt", "relative");
+    let err = mock.export_to_dir(playground.join("colliding")).unwrap_err();
+    assert_eq!(ErrorKind::AlreadyExists, err.kind());
+    assert!(!playground.join("colliding").exists());
+}
+
+#[test]
+#[file_serial]
+fn export_mock_on_panic() {
+    let playground_name = utils::ensure_playground(true);
+    let export_dir = Path::new(&playground_name).join("panic_export");
+
+    let panic_export_dir = export_dir.clone();
+    let result = std::thread::spawn(move || {
+        let mut mock = MockTextHandler::new();
+        mock.export_on_panic(panic_export_dir);
+        mock.write_text(OsStr::new("result.txt"), String::from("wrong")).unwrap();
+
+        assert_eq!("right", mock.read_text(OsStr::new("result.txt")).unwrap());
+    }).join();
+
+    assert!(result.is_err());
+    assert_eq!("wrong", FileTextHandler::new().read_text(export_dir.join("result.txt").as_os_str()).unwrap());
+}
+
+#[test]
+#[file_serial]
+fn diff_file_and_mock() {
+    let playground_name = utils::ensure_playground(true);
+    let playground = Path::new(&playground_name);
+
+    let mut fth = FileTextHandler::new().with_root(playground).with_parent_dirs(true);
+    fth.write_text(OsStr::new("out/a.txt"), String::from("alpha\n")).unwrap();
+    fth.write_text(OsStr::new("out/b.txt"), String::from("beta\n")).unwrap();
+
+    let expected = MockTextHandler::from_txtar("-- out/a.txt --\nalpha\n-- out/b.txt --\nbeta\n");
+    assert!(diff_handlers(&expected, &fth, OsStr::new("out")).unwrap().is_empty());
+
+    fth.write_text(OsStr::new("out/b.txt"), String::from("gamma\n")).unwrap();
+    let diff = diff_handlers(&expected, &fth, OsStr::new("out")).unwrap();
+    assert_eq!(vec!["out/b.txt"], diff.changed_names());
+    assert_eq!("@@ -1,1 +1,1 @@\n-beta\n+gamma\n", diff.modified[0].1);
+}
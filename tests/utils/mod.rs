@@ -5,6 +5,7 @@ use std::str::FromStr;
 
 pub const TESTFILES_DIR: &str = "./tests/playground";
 
+#[allow(clippy::needless_borrows_for_generic_args)]
 pub fn ensure_playground(remove_first: bool) -> OsString {
     let dir_path = Path::new(&TESTFILES_DIR);
 